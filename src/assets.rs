@@ -0,0 +1,134 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// A file next to a post that the post links to, i.e. an image in a bundle:
+/// `posts/2018-05-01-expanding-swifts-reach/index.org` and `images/swift.png`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuneAsset {
+    /// The link as it is written in the html of the post
+    pub link: String,
+    /// The file on disk
+    pub file: PathBuf,
+    /// Where the file goes, relative to the folder of the rendered post
+    pub target: String,
+}
+
+/// The relative `src` and `href` links of the html that point to existing
+/// files in `folder`. Absolute urls and anchors are left alone.
+pub fn find_assets(html: &str, folder: &Path) -> Vec<DuneAsset> {
+    let mut assets: Vec<DuneAsset> = Vec::new();
+    for link in links(html) {
+        if assets.iter().any(|asset| asset.link == link) {
+            continue;
+        }
+        if let Some(asset) = find_asset(link, folder) {
+            assets.push(asset);
+        }
+    }
+    assets
+}
+
+/// The asset for a single link, i.e. the screenshot of a project
+pub fn find_asset(link: &str, folder: &Path) -> Option<DuneAsset> {
+    if !is_relative(link) {
+        return None;
+    }
+    // `image.png?v=2#top` is still `image.png`
+    let name = link.split(|c| c == '?' || c == '#').next().unwrap_or(link);
+    let file = folder.join(name.replace("%20", " "));
+    if name.is_empty() || !file.is_file() {
+        return None;
+    }
+    let is_inside = Path::new(name).components().all(|component| match component {
+        Component::Normal(_) | Component::CurDir => true,
+        _ => false,
+    });
+    // `../shared/image.png` can't go outside of the post folder
+    let target = match is_inside {
+        true => name.trim_left_matches("./").to_owned(),
+        false => file.file_name().and_then(|n| n.to_str())?.to_owned(),
+    };
+    Some(DuneAsset {
+        link: link.to_owned(),
+        file: file,
+        target: target,
+    })
+}
+
+/// Point the links of the assets to `<url>/<target>`, where `url` is the folder of the post
+pub fn rewrite(html: &str, assets: &[DuneAsset], url: &str) -> String {
+    let url = url.trim_right_matches('/');
+    let mut html = html.to_owned();
+    for asset in assets {
+        let replacement = format!("=\"{}/{}\"", url, asset.target);
+        for attribute in &["src", "href"] {
+            html = html.replace(&format!("{}=\"{}\"", attribute, asset.link),
+                                &format!("{}{}", attribute, replacement));
+        }
+    }
+    html
+}
+
+/// The new url for a single link, if it is one of the assets
+pub fn rewrite_link(link: &str, assets: &[DuneAsset], url: &str) -> String {
+    match assets.iter().find(|asset| asset.link == link) {
+        Some(asset) => format!("{}/{}", url.trim_right_matches('/'), asset.target),
+        None => link.to_owned(),
+    }
+}
+
+/// The url of the folder of a post, for `/2018/05/01/reach/` or `/2018/reach.html`
+pub fn folder_url(post_url: &str) -> &str {
+    match post_url.ends_with(".html") {
+        true => post_url.rfind('/').map(|index| &post_url[..index]).unwrap_or(""),
+        false => post_url.trim_right_matches('/'),
+    }
+}
+
+/// Copy the assets into `folder`
+pub fn copy(folder: &Path, assets: &[DuneAsset]) -> io::Result<()> {
+    for asset in assets {
+        let path = folder.join(&asset.target);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&asset.file, &path)?;
+    }
+    Ok(())
+}
+
+fn links(html: &str) -> Vec<&str> {
+    let mut links = Vec::new();
+    for attribute in &["src=\"", "href=\""] {
+        let mut rest = html;
+        while let Some(start) = rest.find(attribute) {
+            rest = &rest[start + attribute.len()..];
+            match rest.find('"') {
+                Some(end) => links.push(&rest[..end]),
+                None => break,
+            }
+        }
+    }
+    links
+}
+
+fn is_relative(link: &str) -> bool {
+    !(link.contains("://") || link.starts_with('/') || link.starts_with('#')
+      || link.starts_with("mailto:") || link.starts_with("data:"))
+}
+
+#[test]
+fn test_assets() {
+    let folder = ::std::env::temp_dir().join("wanderdune-test-assets");
+    fs::create_dir_all(folder.join("images")).unwrap();
+    fs::File::create(folder.join("images/swift.png")).unwrap();
+    let html = "<img src=\"images/swift.png\"/><a href=\"https://swift.org\">Swift</a><img src=\"missing.png\"/>";
+    let assets = find_assets(html, &folder);
+    assert_eq!(assets.len(), 1);
+    assert_eq!(assets[0].target, "images/swift.png");
+    assert_eq!(rewrite(html, &assets, folder_url("/2018/05/01/expanding-swifts-reach/")),
+               "<img src=\"/2018/05/01/expanding-swifts-reach/images/swift.png\"/>\
+                <a href=\"https://swift.org\">Swift</a><img src=\"missing.png\"/>");
+    assert_eq!(folder_url("/2018/reach.html"), "/2018");
+}
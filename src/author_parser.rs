@@ -0,0 +1,145 @@
+use assets::{find_asset, find_assets};
+use dune_base::DuneAuthor;
+use dune_diagnostic::DuneParseResult;
+use org_parser::org_error::{OrgError, OrgErrorKind};
+use page_parser::{page_files, render_file};
+use renderer::DuneRenderers;
+use traits::DunePostSource;
+
+use std::path::{Path, PathBuf};
+
+/// Parses the authors, org or markdown files with `name`, `avatar`,
+/// `email`, `website`, `twitter` and `github`. The text is the bio.
+pub struct AuthorParser {
+    folder: PathBuf,
+    renderers: DuneRenderers,
+}
+
+impl AuthorParser {
+    pub fn new<T: AsRef<Path>>(folder: T) -> AuthorParser {
+        AuthorParser {
+            folder: folder.as_ref().to_owned(),
+            renderers: DuneRenderers::default(),
+        }
+    }
+
+    pub fn renderers(mut self, renderers: DuneRenderers) -> Self {
+        self.renderers = renderers;
+        self
+    }
+
+    fn parse_author(&self, path: &Path, result: &mut DuneParseResult) -> Result<DuneAuthor, OrgError> {
+        let (identifier, fileinfo, contents) = render_file(path, &self.renderers)?;
+        for warning in fileinfo.metadata_warnings() {
+            result.warning(path, warning);
+        }
+        let folder = path.parent().unwrap_or(Path::new("."));
+
+        let mut assets = find_assets(&contents, folder);
+        let avatar = match fileinfo.meta_entry("avatar") {
+            Some(entry) if !entry.value.is_empty() => {
+                let value = entry.value.clone();
+                if value.contains("://") || value.starts_with('/') {
+                    Some(value)
+                } else if let Some(asset) = find_asset(&value, folder) {
+                    assets.push(asset);
+                    Some(value)
+                } else {
+                    result.warning(path, OrgError::new(
+                        OrgErrorKind::MissingAsset,
+                        format!("The avatar {} is not next to the author", value)).line(entry.line));
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        let value = |key: &str| match fileinfo.meta_contents(key) {
+            Some(n) if !n.trim().is_empty() => Some(n.trim().to_owned()),
+            _ => None,
+        };
+        let name = value("name").or(fileinfo.title()).unwrap_or(identifier.clone());
+        Ok(DuneAuthor {
+            identifier: identifier,
+            source: path.to_owned(),
+            name: name,
+            avatar: avatar,
+            email: value("email"),
+            website: value("website"),
+            twitter: value("twitter").map(|n| n.trim_left_matches('@').to_owned()),
+            github: value("github"),
+            bio: contents,
+            assets: assets,
+        })
+    }
+}
+
+impl DunePostSource for AuthorParser {
+    fn parse(&self) -> DuneParseResult {
+        let mut result = DuneParseResult::new();
+        // Blogs with one author don't need profiles
+        if !self.folder.is_dir() {
+            return result;
+        }
+        let paths = match page_files(&self.folder) {
+            Ok(n) => n,
+            Err(e) => {
+                result.error(&self.folder, e);
+                return result;
+            }
+        };
+        for path in paths {
+            match self.parse_author(&path, &mut result) {
+                Ok(author) => result.authors.push(author),
+                Err(e) => result.error(&path, e),
+            }
+        }
+        result
+    }
+}
+
+/// Warn about posts with an `#+author:` that has no profile. Only
+/// checked if there are profiles, a blog without can use any name.
+pub fn check_authors(result: &mut DuneParseResult) {
+    if result.authors.is_empty() {
+        return;
+    }
+    let mut warnings: Vec<(PathBuf, OrgError)> = Vec::new();
+    for post in &result.posts {
+        for author in &post.authors {
+            if !result.authors.iter().any(|other| other.is_named(author)) {
+                warnings.push((post.source.clone(), OrgError::new(
+                    OrgErrorKind::MalformedMetadata,
+                    format!("There is no author {} in the authors folder", author))));
+            }
+        }
+    }
+    for (path, warning) in warnings {
+        result.warning(path, warning);
+    }
+}
+
+#[test]
+fn test_parse_authors() {
+    use std::fs::{self, File};
+    use std::io::prelude::*;
+    let folder = ::std::env::temp_dir().join("wanderdune-test-authors");
+    fs::create_dir_all(&folder).unwrap();
+    File::create(folder.join("terhechte.org")).unwrap()
+        .write_all(b"#+name: Benedikt Terhechte\n#+twitter: @terhechte\n#+avatar: https://appventure.me/img/benedikt.jpg\n\n\
+                     Writes about Swift\n").unwrap();
+    let result = AuthorParser::new(&folder).parse();
+    assert_eq!(result.authors.len(), 1);
+    assert_eq!(result.authors[0].identifier, "terhechte");
+    assert_eq!(result.authors[0].name, "Benedikt Terhechte");
+    assert_eq!(result.authors[0].twitter, Some("terhechte".to_owned()));
+    assert!(result.diagnostics.is_empty());
+    assert!(result.authors[0].is_named("benedikt terhechte"));
+
+    use org_parser::fileinfo::{FileInfo, FileMeta};
+    let fileinfo = FileInfo {
+        meta: vec![FileMeta { key_name: "author".to_owned(), value: "Benedikt Terhechte, jane".to_owned(), line: None }],
+        sources: Vec::new(),
+    };
+    assert_eq!(fileinfo.authors(), vec!["Benedikt Terhechte", "jane"]);
+}
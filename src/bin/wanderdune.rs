@@ -1,5 +1,45 @@
 extern crate wanderdune;
 
+use std::env;
+use std::path::Path;
+use std::process;
+use std::rc::Rc;
+
+use wanderdune::{CacheDB, Configuration, FolderConfiguration};
+
+fn cache(configuration: &Configuration, command: Option<&str>) -> Result<(), Box<std::error::Error>> {
+    // A corrupt cache can't be opened, so it is cleared without opening it
+    if command == Some("clear") {
+        CacheDB::clear_file(configuration.cache_file())?;
+        println!("Removed {}", configuration.cache_file().display());
+        return Ok(());
+    }
+    let cache = CacheDB::open(configuration.cache_file())?;
+    match command {
+        Some("stats") => println!("{}", cache.stats()),
+        Some("prune") => {
+            for identifier in cache.prune(configuration.post_folder())? {
+                println!("Removed {}", identifier);
+            }
+        }
+        _ => println!("Usage: wanderdune [--strict] | wanderdune snippets [export-folder] | wanderdune cache [stats|prune|clear]"),
+    }
+    Ok(())
+}
+
 fn main() {
-    wanderdune::make();
+    let (flags, args): (Vec<String>, Vec<String>) = env::args().skip(1)
+        .partition(|arg| arg.starts_with("--"));
+    let mut configuration = FolderConfiguration::new("Appventure.me", ".");
+    // Fail on posts that can't be built instead of skipping them
+    configuration.set_strict(flags.iter().any(|flag| flag == "--strict"));
+    let result = match args.get(0).map(|n| n.as_str()) {
+        Some("cache") => cache(&configuration, args.get(1).map(|n| n.as_str())),
+        Some("snippets") => wanderdune::snippets(&configuration, args.get(1).map(Path::new)),
+        _ => wanderdune::make(Rc::new(configuration)),
+    };
+    if let Err(e) = result {
+        println!("Error: {}", e);
+        process::exit(1);
+    }
 }
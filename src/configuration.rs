@@ -1,8 +1,182 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use chrono::FixedOffset;
+
+use taxonomy::DuneTaxonomy;
 
 pub trait Configuration {
     fn blog_name(&self) -> &str;
     fn html_folder(&self) -> &str;
     fn post_folder(&self) -> &Path;
     fn cache_file(&self) -> &Path;
+    /// How many posts are parsed and rendered at the same time
+    fn max_threads(&self) -> i32 {
+        4
+    }
+    /// Fail the build if any post could not be built
+    fn strict(&self) -> bool {
+        false
+    }
+    /// Build drafts and scheduled posts, for a local preview. Only
+    /// builds that `execute` a writer render them, `make` only parses
+    fn include_drafts(&self) -> bool {
+        false
+    }
+    /// The timezone of post dates that don't have an offset
+    fn timezone(&self) -> FixedOffset {
+        FixedOffset::east(0)
+    }
+    /// Images and other files of the posts, i.e. the feature images
+    fn content_folder(&self) -> &Path {
+        Path::new("content")
+    }
+    /// The static pages without a date, i.e. About or Talks
+    fn page_folder(&self) -> &Path {
+        Path::new("pages")
+    }
+    /// The projects, i.e. apps and libraries
+    fn project_folder(&self) -> &Path {
+        Path::new("projects")
+    }
+    /// The profiles of the authors
+    fn author_folder(&self) -> &Path {
+        Path::new("authors")
+    }
+    /// The url under which the `content_folder` is served
+    fn content_url(&self) -> &str {
+        "/content"
+    }
+    /// The address of the blog, for absolute links in feeds and social cards
+    fn base_url(&self) -> &str {
+        ""
+    }
+    /// The language for dates in templates, `en` or `de`
+    fn language(&self) -> &str {
+        "en"
+    }
+    /// How many related posts a post links to
+    fn related_count(&self) -> usize {
+        3
+    }
+    /// Tags that don't make posts related, i.e. `blog`
+    fn related_excluded_tags(&self) -> &[String] {
+        &[]
+    }
+    /// Tag aliases, tag names and categories
+    fn taxonomy(&self) -> DuneTaxonomy {
+        DuneTaxonomy::new()
+    }
+}
+
+/// A blog where everything lives in one folder: the `posts`,
+/// their `content`, the `pages`, the `projects`, the `authors`, the generated `html` and the `cache.db`.
+pub struct FolderConfiguration {
+    blog_name: String,
+    html_folder: String,
+    post_folder: PathBuf,
+    content_folder: PathBuf,
+    page_folder: PathBuf,
+    project_folder: PathBuf,
+    author_folder: PathBuf,
+    cache_file: PathBuf,
+    strict: bool,
+    include_drafts: bool,
+    related_count: usize,
+    related_excluded_tags: Vec<String>,
+    taxonomy: DuneTaxonomy,
+}
+
+impl FolderConfiguration {
+    pub fn new<T: AsRef<Path>>(blog_name: &str, folder: T) -> FolderConfiguration {
+        let folder = folder.as_ref();
+        FolderConfiguration {
+            blog_name: blog_name.to_owned(),
+            html_folder: folder.join("html").to_string_lossy().into_owned(),
+            post_folder: folder.join("posts"),
+            content_folder: folder.join("content"),
+            page_folder: folder.join("pages"),
+            project_folder: folder.join("projects"),
+            author_folder: folder.join("authors"),
+            cache_file: folder.join("cache.db"),
+            strict: false,
+            include_drafts: false,
+            related_count: 3,
+            related_excluded_tags: Vec::new(),
+            taxonomy: DuneTaxonomy::new(),
+        }
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn set_include_drafts(&mut self, include_drafts: bool) {
+        self.include_drafts = include_drafts;
+    }
+
+    pub fn set_related_count(&mut self, related_count: usize) {
+        self.related_count = related_count;
+    }
+
+    pub fn set_related_excluded_tags(&mut self, tags: &[&str]) {
+        self.related_excluded_tags = tags.iter().map(|tag| tag.to_string()).collect();
+    }
+
+    pub fn set_taxonomy(&mut self, taxonomy: DuneTaxonomy) {
+        self.taxonomy = taxonomy;
+    }
+}
+
+impl Configuration for FolderConfiguration {
+    fn blog_name(&self) -> &str {
+        &self.blog_name
+    }
+
+    fn html_folder(&self) -> &str {
+        &self.html_folder
+    }
+
+    fn post_folder(&self) -> &Path {
+        &self.post_folder
+    }
+
+    fn cache_file(&self) -> &Path {
+        &self.cache_file
+    }
+
+    fn content_folder(&self) -> &Path {
+        &self.content_folder
+    }
+
+    fn page_folder(&self) -> &Path {
+        &self.page_folder
+    }
+
+    fn project_folder(&self) -> &Path {
+        &self.project_folder
+    }
+
+    fn author_folder(&self) -> &Path {
+        &self.author_folder
+    }
+
+    fn strict(&self) -> bool {
+        self.strict
+    }
+
+    fn include_drafts(&self) -> bool {
+        self.include_drafts
+    }
+
+    fn related_count(&self) -> usize {
+        self.related_count
+    }
+
+    fn related_excluded_tags(&self) -> &[String] {
+        &self.related_excluded_tags
+    }
+
+    fn taxonomy(&self) -> DuneTaxonomy {
+        self.taxonomy.clone()
+    }
 }
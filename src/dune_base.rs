@@ -1,6 +1,13 @@
+use std::fmt;
+use chrono::{DateTime, FixedOffset};
+use std::path::PathBuf;
 use std::rc::Rc;
 
-use dune_post::DunePost;
+use assets::DuneAsset;
+use dune_post::{DunePost, DunePublication};
+use dune_index::DuneIndex;
+use taxonomy::{DuneCategory, DuneTaxonomy};
+use dune_page::DuneStaticPage;
 use configuration::Configuration;
 
 #[derive(Debug)]
@@ -9,14 +16,305 @@ pub struct DuneGroup {
     pub count: usize,
 }
 
-#[derive(Debug)]
-pub struct DuneProject;
+/// Posts with the same `#+series:`, ordered by `#+series_part:` and date
+#[derive(Debug, Clone)]
+pub struct DuneSeries {
+    /// The slug of the name, for the url of the series
+    pub identifier: String,
+    pub title: String,
+    /// The identifiers of the posts, in order
+    pub posts: Vec<String>,
+}
+
+impl DuneSeries {
+    pub fn len(&self) -> usize {
+        self.posts.len()
+    }
+
+    /// The part of the post, starting with 1
+    pub fn part(&self, post: &DunePost) -> Option<usize> {
+        self.posts.iter().position(|identifier| identifier == &post.identifier).map(|index| index + 1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuneProjectStatus {
+    /// Still being worked on
+    Active,
+    /// Done, but bugs are still fixed
+    Maintained,
+    /// Not worked on anymore
+    Archived,
+}
+
+impl DuneProjectStatus {
+    pub fn from_name(name: &str) -> Option<DuneProjectStatus> {
+        match name.trim().to_lowercase().as_str() {
+            "active" => Some(DuneProjectStatus::Active),
+            "maintained" => Some(DuneProjectStatus::Maintained),
+            "archived" => Some(DuneProjectStatus::Archived),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for DuneProjectStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DuneProjectStatus::Active => write!(f, "active"),
+            DuneProjectStatus::Maintained => write!(f, "maintained"),
+            DuneProjectStatus::Archived => write!(f, "archived"),
+        }
+    }
+}
+
+/// A project from the `projects` folder, i.e. an app or a library
+#[derive(Debug, Clone)]
+pub struct DuneProject {
+    /// The name of the file without extension
+    pub identifier: String,
+    /// The file the project was parsed from
+    pub source: PathBuf,
+    pub name: String,
+    pub summary: String,
+    pub repository: Option<String>,
+    pub status: DuneProjectStatus,
+    /// Posts with one of these tags are linked from the project and back
+    pub tags: Vec<String>,
+    /// The url of the screenshot, a full url or a file next to the project
+    pub screenshot: Option<String>,
+    pub contents: String,
+    /// Files next to the project that it links to, copied with the project
+    pub assets: Vec<DuneAsset>,
+}
+
+/// An author from the `authors` folder, posts refer to the identifier
+#[derive(Debug, Clone)]
+pub struct DuneAuthor {
+    /// The name of the file without extension
+    pub identifier: String,
+    /// The file the author was parsed from
+    pub source: PathBuf,
+    pub name: String,
+    /// The url of the avatar, a full url or a file next to the author
+    pub avatar: Option<String>,
+    pub email: Option<String>,
+    pub website: Option<String>,
+    /// The user names, without `@`
+    pub twitter: Option<String>,
+    pub github: Option<String>,
+    /// The html of the file
+    pub bio: String,
+    /// Files next to the author that the bio links to
+    pub assets: Vec<DuneAsset>,
+}
+
+impl DuneAuthor {
+    /// Whether `#+author:` means this author, by identifier or by name
+    pub fn is_named(&self, author: &str) -> bool {
+        let author = author.to_lowercase();
+        author == self.identifier.to_lowercase() || author == self.name.to_lowercase()
+    }
+}
 
 pub struct DuneBase {
     pub posts: Vec<DunePost>,
+    pub pages: Vec<DuneStaticPage>,
     pub projects: Vec<DuneProject>,
+    pub authors: Vec<DuneAuthor>,
     pub tags: Vec<DuneGroup>,
     pub keywords: Vec<DuneGroup>,
+    pub series: Vec<DuneSeries>,
+    /// For the queries, built from `posts`
+    pub index: DuneIndex,
+    /// The positions of the related posts of every post, best first
+    pub related: Vec<Vec<usize>>,
+    /// The tags of the posts are already normalized with it
+    pub taxonomy: DuneTaxonomy,
     pub configuration: Rc<Configuration>,
 }
 
+/// The queries return listed posts, newest first. Only `post` and
+/// `posts_with_publication` also find unlisted posts.
+impl DuneBase {
+    pub fn post(&self, identifier: &str) -> Option<&DunePost> {
+        self.index.identifier(identifier).map(|position| &self.posts[position])
+    }
+
+    pub fn posts_tagged(&self, tag: &str) -> Vec<&DunePost> {
+        self.listed(self.index.tag(tag))
+    }
+
+    pub fn posts_with_keyword(&self, keyword: &str) -> Vec<&DunePost> {
+        self.listed(self.index.keyword(keyword))
+    }
+
+    pub fn posts_in_year(&self, year: i32) -> Vec<&DunePost> {
+        self.listed(&self.index.year(year))
+    }
+
+    pub fn posts_in_month(&self, year: i32, month: u32) -> Vec<&DunePost> {
+        self.listed(self.index.month(year, month))
+    }
+
+    /// The posts released from `from` up to and including `to`
+    pub fn posts_between(&self, from: &DateTime<FixedOffset>, to: &DateTime<FixedOffset>) -> Vec<&DunePost> {
+        self.listed(&self.index.between(from.timestamp(), to.timestamp()))
+    }
+
+    pub fn posts_with_publication(&self, publication: DunePublication) -> Vec<&DunePost> {
+        self.index.publication(publication).iter()
+            .map(|&position| &self.posts[position])
+            .collect()
+    }
+
+    /// The newest `count` posts
+    pub fn recent(&self, count: usize) -> Vec<&DunePost> {
+        self.index.newest_first()
+            .map(|position| &self.posts[position])
+            .filter(|post| post.is_listed())
+            .take(count)
+            .collect()
+    }
+
+    /// The posts related to this one by tags, keywords and text
+    pub fn related_to(&self, post: &DunePost) -> Vec<&DunePost> {
+        match self.index.identifier(&post.identifier) {
+            Some(position) => self.related[position].iter().map(|&other| &self.posts[other]).collect(),
+            None => Vec::new()
+        }
+    }
+
+    /// The name of a tag, i.e. `iOS` for `ios`
+    pub fn tag_name(&self, tag: &str) -> String {
+        match self.taxonomy.tag_info(tag) {
+            Some(info) => info.name.clone(),
+            None => tag.to_owned()
+        }
+    }
+
+    pub fn tag_description(&self, tag: &str) -> String {
+        self.taxonomy.tag_info(tag).map(|info| info.description.clone()).unwrap_or_default()
+    }
+
+    pub fn category(&self, identifier: &str) -> Option<&DuneCategory> {
+        self.taxonomy.category_by_identifier(identifier)
+    }
+
+    /// The categories that are not below another category
+    pub fn categories(&self) -> Vec<&DuneCategory> {
+        self.taxonomy.children(None)
+    }
+
+    pub fn subcategories(&self, category: &DuneCategory) -> Vec<&DuneCategory> {
+        self.taxonomy.children(Some(&category.identifier))
+    }
+
+    /// The posts with a tag of the category or of a category below it
+    pub fn posts_in_category(&self, category: &DuneCategory) -> Vec<&DunePost> {
+        let mut positions: Vec<usize> = self.taxonomy.category_tags(&category.identifier).iter()
+            .flat_map(|tag| self.index.tag(tag).iter().cloned())
+            .collect();
+        positions.sort();
+        positions.dedup();
+        self.listed(&positions)
+    }
+
+    /// The author with the identifier or the name
+    pub fn author(&self, author: &str) -> Option<&DuneAuthor> {
+        self.authors.iter().find(|other| other.is_named(author))
+    }
+
+    /// The authors of the post, unknown ones are left out
+    pub fn authors_of(&self, post: &DunePost) -> Vec<&DuneAuthor> {
+        post.authors.iter().filter_map(|identifier| self.author(identifier)).collect()
+    }
+
+    pub fn posts_by(&self, author: &DuneAuthor) -> Vec<&DunePost> {
+        let mut positions: Vec<usize> = self.index.author(&author.identifier).iter()
+            .chain(self.index.author(&author.name).iter())
+            .cloned()
+            .collect();
+        positions.sort();
+        positions.dedup();
+        self.listed(&positions)
+    }
+
+    fn listed(&self, positions: &[usize]) -> Vec<&DunePost> {
+        let mut posts: Vec<&DunePost> = positions.iter()
+            .map(|&position| &self.posts[position])
+            .filter(|post| post.is_listed())
+            .collect();
+        posts.sort();
+        posts.reverse();
+        posts
+    }
+
+    /// The pages with a `#+menu:` position, in that order
+    pub fn menu(&self) -> Vec<&DuneStaticPage> {
+        let mut pages: Vec<&DuneStaticPage> = self.pages.iter()
+            .filter(|page| page.menu().is_some())
+            .collect();
+        pages.sort_by_key(|page| page.menu());
+        pages
+    }
+
+    /// The listed posts that share a tag with the project, newest first
+    pub fn posts_for_project(&self, project: &DuneProject) -> Vec<&DunePost> {
+        let mut positions: Vec<usize> = project.tags.iter()
+            .flat_map(|tag| self.index.tag(tag).iter().cloned())
+            .collect();
+        positions.sort();
+        positions.dedup();
+        self.listed(&positions)
+    }
+
+    /// The projects that share a tag with the post
+    pub fn projects_for_post(&self, post: &DunePost) -> Vec<&DuneProject> {
+        self.projects.iter()
+            .filter(|project| project.tags.iter().any(|tag| post.tags.contains(tag)))
+            .collect()
+    }
+
+    pub fn series_for(&self, post: &DunePost) -> Option<&DuneSeries> {
+        self.series.iter().find(|series| series.part(post).is_some())
+    }
+
+    /// The part before the post in its series
+    pub fn series_previous(&self, post: &DunePost) -> Option<&DunePost> {
+        let series = self.series_for(post)?;
+        let part = series.part(post)?;
+        match part > 1 {
+            true => self.post(&series.posts[part - 2]),
+            false => None
+        }
+    }
+
+    /// The part after the post in its series
+    pub fn series_next(&self, post: &DunePost) -> Option<&DunePost> {
+        let series = self.series_for(post)?;
+        let part = series.part(post)?;
+        series.posts.get(part).and_then(|identifier| self.post(identifier))
+    }
+
+    /// The language of the post, or the language of the blog
+    pub fn language_of<'a>(&'a self, post: &'a DunePost) -> &'a str {
+        match post.language {
+            Some(ref language) => language,
+            None => self.configuration.language()
+        }
+    }
+
+    /// The post and its translations, or nothing if the post isn't translated.
+    /// Translations share the identifier of the original in `#+translation_of:`.
+    pub fn translations(&self, post: &DunePost) -> Vec<&DunePost> {
+        let original = post.translation_of.as_ref().unwrap_or(&post.identifier);
+        let posts: Vec<&DunePost> = self.posts.iter()
+            .filter(|other| other.translation_of.as_ref().unwrap_or(&other.identifier) == original)
+            .collect();
+        match posts.len() > 1 {
+            true => posts,
+            false => Vec::new()
+        }
+    }
+}
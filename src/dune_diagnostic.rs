@@ -0,0 +1,97 @@
+use dune_post::DunePost;
+use dune_page::DuneStaticPage;
+use dune_base::{DuneAuthor, DuneProject};
+use org_parser::org_error::{OrgError, OrgErrorKind};
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuneSeverity {
+    /// The post was built, but something about it looks wrong
+    Warning,
+    /// The post was skipped
+    Error,
+}
+
+/// Something that went wrong while parsing a post
+#[derive(Debug)]
+pub struct DuneDiagnostic {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub severity: DuneSeverity,
+    pub error: OrgError,
+}
+
+impl DuneDiagnostic {
+    pub fn kind(&self) -> OrgErrorKind {
+        self.error.kind
+    }
+}
+
+impl fmt::Display for DuneDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            DuneSeverity::Warning => "warning",
+            DuneSeverity::Error => "error",
+        };
+        match self.line {
+            Some(line) => write!(f, "{}: {}:{}: {}", severity, self.path.display(), line, self.error.message),
+            None => write!(f, "{}: {}: {}", severity, self.path.display(), self.error.message),
+        }
+    }
+}
+
+/// The posts (or pages, projects, authors) of a `DunePostSource` and everything that
+/// went wrong while parsing them
+#[derive(Debug)]
+pub struct DuneParseResult {
+    pub posts: Vec<DunePost>,
+    pub pages: Vec<DuneStaticPage>,
+    pub projects: Vec<DuneProject>,
+    pub authors: Vec<DuneAuthor>,
+    pub diagnostics: Vec<DuneDiagnostic>,
+}
+
+impl DuneParseResult {
+    pub fn new() -> DuneParseResult {
+        DuneParseResult {
+            posts: Vec::new(),
+            pages: Vec::new(),
+            projects: Vec::new(),
+            authors: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn error<T: AsRef<Path>>(&mut self, path: T, error: OrgError) {
+        self.diagnose(path, DuneSeverity::Error, error);
+    }
+
+    pub fn warning<T: AsRef<Path>>(&mut self, path: T, error: OrgError) {
+        self.diagnose(path, DuneSeverity::Warning, error);
+    }
+
+    fn diagnose<T: AsRef<Path>>(&mut self, path: T, severity: DuneSeverity, error: OrgError) {
+        self.diagnostics.push(DuneDiagnostic {
+            path: path.as_ref().to_owned(),
+            line: error.line,
+            severity,
+            error,
+        });
+    }
+
+    pub fn extend(&mut self, other: DuneParseResult) {
+        self.posts.extend(other.posts);
+        self.pages.extend(other.pages);
+        self.projects.extend(other.projects);
+        self.authors.extend(other.authors);
+        self.diagnostics.extend(other.diagnostics);
+    }
+
+    pub fn errors(&self) -> usize {
+        self.diagnostics.iter()
+            .filter(|diagnostic| diagnostic.severity == DuneSeverity::Error)
+            .count()
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::Datelike;
+
+use dune_post::{DunePost, DunePublication};
+
+/// Positions of the posts of a `DuneBase`, so that queries don't
+/// have to look at every post. Built once, the posts don't change.
+#[derive(Debug, Default)]
+pub struct DuneIndex {
+    identifiers: HashMap<String, usize>,
+    tags: HashMap<String, Vec<usize>>,
+    keywords: HashMap<String, Vec<usize>>,
+    /// By the lowercase `#+author:`
+    authors: HashMap<String, Vec<usize>>,
+    /// Year and month
+    months: BTreeMap<(i32, u32), Vec<usize>>,
+    /// All posts, oldest first, with their timestamps
+    chronological: Vec<(i64, usize)>,
+    /// Newest first
+    publications: HashMap<DunePublication, Vec<usize>>,
+}
+
+impl DuneIndex {
+    pub fn new(posts: &[DunePost]) -> DuneIndex {
+        let mut index = DuneIndex::default();
+        for (position, post) in posts.iter().enumerate() {
+            index.identifiers.insert(post.identifier.clone(), position);
+            for tag in &post.tags {
+                index.tags.entry(tag.clone()).or_insert(Vec::new()).push(position);
+            }
+            for keyword in &post.keywords {
+                index.keywords.entry(keyword.clone()).or_insert(Vec::new()).push(position);
+            }
+            for author in &post.authors {
+                index.authors.entry(author.to_lowercase()).or_insert(Vec::new()).push(position);
+            }
+            let date = &post.released.date;
+            index.months.entry((date.year(), date.month())).or_insert(Vec::new()).push(position);
+            index.chronological.push((post.released.timestamp(), position));
+        }
+        // Posts with the same timestamp are ordered like `DunePost` orders them
+        index.chronological.sort_by(|a, b| posts[a.1].cmp(&posts[b.1]));
+        for &(_, position) in index.chronological.iter().rev() {
+            index.publications.entry(posts[position].publication).or_insert(Vec::new()).push(position);
+        }
+        index
+    }
+
+    pub fn identifier(&self, identifier: &str) -> Option<usize> {
+        self.identifiers.get(identifier).cloned()
+    }
+
+    pub fn tag(&self, tag: &str) -> &[usize] {
+        self.tags.get(tag).map(|positions| positions.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn keyword(&self, keyword: &str) -> &[usize] {
+        self.keywords.get(keyword).map(|positions| positions.as_slice()).unwrap_or(&[])
+    }
+
+    /// The posts of an author, by identifier or by name in any case
+    pub fn author(&self, author: &str) -> &[usize] {
+        self.authors.get(&author.to_lowercase()).map(|positions| positions.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn month(&self, year: i32, month: u32) -> &[usize] {
+        self.months.get(&(year, month)).map(|positions| positions.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn year(&self, year: i32) -> Vec<usize> {
+        self.months.range((year, 1)..(year, 13))
+            .flat_map(|(_, positions)| positions.iter().cloned())
+            .collect()
+    }
+
+    /// The posts from `from` up to and including `to`, oldest first
+    pub fn between(&self, from: i64, to: i64) -> Vec<usize> {
+        let start = match self.chronological.binary_search_by_key(&from, |&(timestamp, _)| timestamp) {
+            // Several posts can have the same timestamp
+            Ok(n) => self.chronological[..n].iter().rposition(|&(timestamp, _)| timestamp < from).map_or(0, |n| n + 1),
+            Err(n) => n,
+        };
+        self.chronological[start..].iter()
+            .take_while(|&&(timestamp, _)| timestamp <= to)
+            .map(|&(_, position)| position)
+            .collect()
+    }
+
+    pub fn publication(&self, publication: DunePublication) -> &[usize] {
+        self.publications.get(&publication).map(|positions| positions.as_slice()).unwrap_or(&[])
+    }
+
+    /// All posts, newest first, without collecting them
+    pub fn newest_first<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
+        self.chronological.iter().rev().map(|&(_, position)| position)
+    }
+
+    /// All posts, oldest first
+    pub fn chronological(&self) -> Vec<usize> {
+        self.chronological.iter().map(|&(_, position)| position).collect()
+    }
+}
+
+#[test]
+fn test_dune_index() {
+    let post = |name: &str, date: &str, tags: &str| DunePost::test_post(name, &[("date", date), ("tags", tags)], "");
+    let posts = vec![post("2018-03-02-generics", "<2018-03-02 Fri>", "swift"),
+                     post("2017-12-24-tuples", "<2017-12-24 Sun>", "swift rust"),
+                     post("2018-01-10-enums", "<2018-01-10 Wed>", "rust")];
+    let index = DuneIndex::new(&posts);
+    assert_eq!(index.identifier("2018-01-10-enums"), Some(2));
+    assert_eq!(index.tag("swift"), &[0, 1]);
+    assert_eq!(index.year(2018), vec![2, 0]);
+    assert_eq!(index.month(2017, 12), &[1]);
+    assert_eq!(index.chronological(), vec![1, 2, 0]);
+    assert_eq!(index.newest_first().take(2).collect::<Vec<usize>>(), vec![0, 2]);
+    assert_eq!(index.publication(DunePublication::Published), &[0, 2, 1]);
+    let from = posts[1].released.timestamp();
+    assert_eq!(index.between(from, posts[2].released.timestamp()), vec![1, 2]);
+}
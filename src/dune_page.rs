@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use assets::DuneAsset;
+use dune_post::DuneMetaValue;
+use org_parser::fileinfo::normalize_key;
+
+/// The template of a static page, chosen with `#+template:`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DunePageTemplate {
+    /// Like a post, next to the sidebar
+    Page,
+    /// The full width, without the sidebar
+    Wide,
+}
+
+impl DunePageTemplate {
+    pub fn from_name(name: &str) -> Option<DunePageTemplate> {
+        match name {
+            "page" => Some(DunePageTemplate::Page),
+            "wide" => Some(DunePageTemplate::Wide),
+            _ => None
+        }
+    }
+}
+
+/// A page without a date from the `pages` folder, i.e. About or Talks.
+/// Pages are not part of the date groupings, the lists or the feeds.
+#[derive(Debug, Clone)]
+pub struct DuneStaticPage {
+    /// The name of the file without extension, `about` for `about.org`
+    pub identifier: String,
+    /// The file the page was parsed from
+    pub source: PathBuf,
+    pub title: String,
+    pub contents: String,
+    pub description: String,
+    pub template: DunePageTemplate,
+    /// Drafts are only built for a preview, like draft posts
+    pub enabled: bool,
+    /// Files next to the page that it links to, copied with the page
+    pub assets: Vec<DuneAsset>,
+    /// All `#+key: value` lines (or front matter keys) of the page,
+    /// with lowercase keys
+    pub meta: BTreeMap<String, DuneMetaValue>
+}
+
+impl DuneStaticPage {
+    pub fn meta_value(&self, key: &str) -> Option<&DuneMetaValue> {
+        self.meta.get(&normalize_key(key))
+    }
+
+    pub fn has_meta(&self, key: &str) -> bool {
+        self.meta_value(key).is_some()
+    }
+
+    /// The metadata value as text, or an empty string if the page doesn't have it
+    pub fn meta(&self, key: &str) -> String {
+        match self.meta_value(key) {
+            Some(value) => value.to_string(),
+            None => String::new()
+        }
+    }
+
+    /// The position in the navigation from `#+menu: 2`.
+    /// Pages without it are not in the navigation.
+    pub fn menu(&self) -> Option<i32> {
+        self.meta("menu").trim().parse::<i32>().ok()
+    }
+}
@@ -1,53 +1,359 @@
 use std::cmp::{Ord, Ordering, PartialOrd, Eq, PartialEq};
-use utils::strip_tags;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime, TimeZone};
+use utils::{strip_tags, truncate_html, truncate_words};
+use assets::DuneAsset;
+use toc::{self, DuneHeading};
+use org_parser::fileinfo::normalize_key;
+
+/// Everything before the marker is the excerpt of a post, `#+more` in org
+pub const MORE_MARKER: &'static str = "<!--more-->";
+
+/// Metadata keys whose values are whitespace separated lists
+const LIST_KEYS: &'static [&'static str] = &["tags", "keywords", "alias", "aliases"];
 
 #[derive(Debug, Clone)]
 pub struct DunePost {
     pub identifier: String,
     pub path: String,
+    /// The file the post was parsed from
+    pub source: PathBuf,
     pub title: String,
     pub released: DunePostTime,
     pub contents: String,
+    /// The headings of the contents, as a tree
+    pub toc: Vec<DuneHeading>,
     pub tags: Vec<String>,
     pub keywords: Vec<String>,
     pub description: String,
-    pub enabled: bool
+    /// Whether the post is published, see `publication`
+    pub enabled: bool,
+    pub publication: DunePublication,
+    /// Old urls that redirect to the post
+    pub aliases: Vec<String>,
+    /// The authors from `#+author:`, the identifier (`terhechte`) or the name of a profile
+    pub authors: Vec<String>,
+    /// The name of the series from `#+series:`, i.e. `Pattern Matching in Swift`
+    pub series: Option<String>,
+    /// The position in the series from `#+series_part:`
+    pub series_part: Option<i32>,
+    /// The language code from `#+language:`, posts without one are
+    /// in the language of the blog
+    pub language: Option<String>,
+    /// The identifier of the post this one translates, from `#+translation_of:`
+    pub translation_of: Option<String>,
+    /// The url of the image for tiles, feeds and social cards
+    pub feature_image: Option<String>,
+    /// Files next to the post that it links to, copied with the post
+    pub assets: Vec<DuneAsset>,
+    /// All `#+key: value` lines (or front matter keys) of the post,
+    /// with lowercase keys
+    pub meta: BTreeMap<String, DuneMetaValue>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DunePublication {
+    /// Only built when drafts are included for a preview
+    Draft,
+    /// Published, but the date is after the build, so it is held back like a draft
+    Scheduled,
+    /// Built, but not part of lists, feeds and sitemaps
+    Unlisted,
+    Published,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DuneMetaValue {
+    Text(String),
+    List(Vec<String>),
+    Bool(bool),
+    Date(DunePostTime),
+}
+
+impl DuneMetaValue {
+    /// Figure out the type of a metadata value. Dates have to
+    /// start with `year-month-day`, i.e. `<2018-04-02 Mon>`, and
+    /// are in `timezone` unless they have an offset.
+    pub fn new(key: &str, value: &str, timezone: &FixedOffset) -> DuneMetaValue {
+        let value = value.trim();
+        if LIST_KEYS.contains(&key) {
+            return DuneMetaValue::List(value.split_whitespace().map(String::from).collect());
+        }
+        match value {
+            "true" => return DuneMetaValue::Bool(true),
+            "false" => return DuneMetaValue::Bool(false),
+            _ => ()
+        }
+        match DunePostTime::parse(value, timezone) {
+            Some(date) => DuneMetaValue::Date(date),
+            None => DuneMetaValue::Text(value.to_owned())
+        }
+    }
+}
+
+impl fmt::Display for DuneMetaValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &DuneMetaValue::Text(ref text) => write!(f, "{}", text),
+            &DuneMetaValue::List(ref items) => write!(f, "{}", items.join(", ")),
+            &DuneMetaValue::Bool(value) => write!(f, "{}", value),
+            &DuneMetaValue::Date(ref date) => write!(f, "{}-{}-{}", date.year, date.month, date.day),
+        }
+    }
 }
 
 impl DunePost {
+    /// The text of the post up to `len` characters, cut on a word boundary
     pub fn shortened_contents(&self, len: usize) -> String {
-        let content_len = self.contents.len();
-        let len = ::std::cmp::min(len, content_len);
-        let mut stripped = strip_tags(&self.contents);
-        stripped.split_off(len).to_string()
+        let stripped = strip_tags(&self.contents);
+        if stripped.chars().count() <= len {
+            return stripped;
+        }
+        let head: String = stripped.chars().take(len + 1).collect();
+        match head.rfind(char::is_whitespace) {
+            Some(index) => head[..index].trim_right().to_owned(),
+            None => head.chars().take(len).collect(),
+        }
+    }
+
+    /// The text before the more marker, or the first `words` words
+    pub fn excerpt(&self, words: usize) -> String {
+        match self.contents.find(MORE_MARKER) {
+            Some(index) => strip_tags(&self.contents[..index]).trim().to_owned(),
+            None => truncate_words(&strip_tags(&self.contents), words),
+        }
+    }
+
+    /// Like `excerpt`, but with the html of the post. Tags that are
+    /// open at the cut are closed.
+    pub fn excerpt_html(&self, words: usize) -> String {
+        match self.contents.find(MORE_MARKER) {
+            Some(index) => truncate_html(&self.contents[..index], usize::max_value()),
+            None => truncate_html(&self.contents, words),
+        }
+    }
+
+    /// The headings of `toc` depth first, for headings at any level
+    pub fn headings(&self) -> Vec<&DuneHeading> {
+        toc::flatten(&self.toc)
+    }
+
+    pub fn has_more(&self) -> bool {
+        self.contents.contains(MORE_MARKER)
+    }
+
+    /// Drafts and scheduled posts are only built for a preview
+    pub fn is_draft(&self) -> bool {
+        self.publication == DunePublication::Draft || self.publication == DunePublication::Scheduled
+    }
+
+    pub fn is_scheduled(&self) -> bool {
+        self.publication == DunePublication::Scheduled
+    }
+
+    /// The feature image as an absolute url, `/content/image.jpg` is
+    /// prefixed with `base_url`, full urls stay as they are
+    pub fn feature_image_url(&self, base_url: &str) -> Option<String> {
+        self.feature_image.as_ref().map(|image| match image.starts_with('/') {
+            true => format!("{}{}", base_url.trim_right_matches('/'), image),
+            false => image.clone(),
+        })
+    }
+
+    /// Whether the post shows up in lists, feeds and sitemaps
+    pub fn is_listed(&self) -> bool {
+        self.publication != DunePublication::Unlisted
+    }
+
+    pub fn meta_value(&self, key: &str) -> Option<&DuneMetaValue> {
+        self.meta.get(&normalize_key(key))
+    }
+
+    pub fn has_meta(&self, key: &str) -> bool {
+        self.meta_value(key).is_some()
+    }
+
+    /// The metadata value as text, or an empty string if the post
+    /// doesn't have it. For templates: `{{ post.meta("summary") }}`
+    pub fn meta(&self, key: &str) -> String {
+        match self.meta_value(key) {
+            Some(value) => value.to_string(),
+            None => String::new()
+        }
+    }
+
+    /// A list value, or the words of a text value
+    pub fn meta_list(&self, key: &str) -> Vec<String> {
+        match self.meta_value(key) {
+            Some(&DuneMetaValue::List(ref items)) => items.clone(),
+            Some(&DuneMetaValue::Text(ref text)) => text.split_whitespace().map(String::from).collect(),
+            _ => Vec::new()
+        }
+    }
+
+    pub fn meta_bool(&self, key: &str) -> bool {
+        match self.meta_value(key) {
+            Some(&DuneMetaValue::Bool(value)) => value,
+            _ => false
+        }
+    }
+
+    pub fn meta_date(&self, key: &str) -> Option<&DunePostTime> {
+        match self.meta_value(key) {
+            Some(&DuneMetaValue::Date(ref date)) => Some(date),
+            _ => None
+        }
     }
 }
 
-#[derive(Debug, Clone)]
+const MONTHS_EN: [&'static str; 12] = ["January", "February", "March", "April", "May", "June", "July",
+                                         "August", "September", "October", "November", "December"];
+const MONTHS_DE: [&'static str; 12] = ["Januar", "Februar", "März", "April", "Mai", "Juni", "Juli",
+                                         "August", "September", "Oktober", "November", "Dezember"];
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct DunePostTime {
     pub year: String,
     pub month: String,
     pub day: String,
-    pub values: (i32, i32, i32)
+    pub values: (i32, i32, i32),
+    /// Posts without a time of day are released at midnight
+    pub has_time: bool,
+    pub date: DateTime<FixedOffset>
 }
 
 impl DunePostTime {
+    pub fn new(year: i32, month: i32, day: i32, time: Option<NaiveTime>, timezone: &FixedOffset) -> Option<DunePostTime> {
+        if month < 1 || day < 1 {
+            return None;
+        }
+        let date = NaiveDate::from_ymd_opt(year, month as u32, day as u32)?;
+        let datetime = date.and_time(time.unwrap_or(NaiveTime::from_hms(0, 0, 0)));
+        let date = timezone.from_local_datetime(&datetime).single()?;
+        Some(DunePostTime::with_date(date, time.is_some()))
+    }
+
+    pub fn with_date(date: DateTime<FixedOffset>, has_time: bool) -> DunePostTime {
+        DunePostTime {
+            year: format!("{:04}", date.year()),
+            month: format!("{:02}", date.month()),
+            day: format!("{:02}", date.day()),
+            values: (date.year(), date.month() as i32, date.day() as i32),
+            has_time: has_time,
+            date: date
+        }
+    }
+
+    /// Parse `2018-04-02`, optionally followed by a time like `10:30` and
+    /// an offset like `+0200`, or wrapped in an org timestamp like
+    /// `<2018-04-02 Mon 10:30>`. RFC 3339 dates work, too.
+    /// Dates without an offset are in `timezone`.
+    pub fn parse(value: &str, timezone: &FixedOffset) -> Option<DunePostTime> {
+        if let Ok(date) = DateTime::parse_from_rfc3339(value.trim()) {
+            return Some(DunePostTime::with_date(date, true));
+        }
+        let is_timestamp = value.starts_with('<') || value.starts_with('[');
+        let value = value.trim_left_matches(|c| c == '<' || c == '[');
+        if value.len() < 10 || !value.is_char_boundary(10) {
+            return None;
+        }
+        // Only a time may follow the date, unless it is an org timestamp
+        let (date, rest) = value.split_at(10);
+        let rest_is_time = rest.is_empty() || rest.starts_with('T')
+            || rest.trim_left().starts_with(|c: char| c.is_digit(10));
+        if !is_timestamp && !rest_is_time {
+            return None;
+        }
+        let components: Vec<&str> = date.split('-').collect();
+        if components.len() != 3 || components[0].len() != 4
+            || components.iter().any(|n| !n.chars().all(|c| c.is_digit(10))) {
+            return None;
+        }
+        let mut time: Option<NaiveTime> = None;
+        let mut timezone = timezone.clone();
+        let rest = rest.trim_left_matches('T').trim_right_matches(|c| c == '>' || c == ']');
+        for token in rest.split_whitespace() {
+            if let Some(parsed) = parse_time(token) {
+                time = Some(parsed);
+            } else if let Some(offset) = parse_offset(token) {
+                timezone = offset;
+            }
+        }
+        match (components[0].parse(), components[1].parse(), components[2].parse()) {
+            (Ok(year), Ok(month), Ok(day)) => DunePostTime::new(year, month, day, time, &timezone),
+            _ => None
+        }
+    }
+
+    /// Seconds since the epoch
     pub fn timestamp(&self) -> i64 {
-        let base_year: i32 = 2010;
-        let year = self.values.0 - base_year;
-        ((year * 12 * 31) + (self.values.1 * 31) + self.values.2) as i64
+        self.date.timestamp()
     }
+
+    /// i.e. `2018-04-12T10:30:00+02:00` for Atom feeds
+    pub fn rfc3339(&self) -> String {
+        self.date.to_rfc3339()
+    }
+
+    /// i.e. `Thu, 12 Apr 2018 10:30:00 +0200` for RSS feeds
+    pub fn rfc2822(&self) -> String {
+        self.date.to_rfc2822()
+    }
+
+    /// Format with `strftime` placeholders, i.e. `%d.%m.%Y`
+    pub fn format(&self, format: &str) -> String {
+        self.date.format(format).to_string()
+    }
+
+    /// The date for readers, i.e. `April 2, 2018` or `2. April 2018`.
+    /// Languages other than `en` and `de` fall back to english.
+    pub fn localized(&self, language: &str) -> String {
+        let month = (self.values.1 - 1) as usize;
+        match language {
+            "de" => format!("{}. {} {}", self.values.2, MONTHS_DE[month], self.values.0),
+            _ => format!("{} {}, {}", MONTHS_EN[month], self.values.2, self.values.0),
+        }
+    }
+}
+
+fn parse_time(token: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(token, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(token, "%H:%M"))
+        .ok()
+}
+
+/// `+0200`, `-05:00` or `Z`
+fn parse_offset(token: &str) -> Option<FixedOffset> {
+    if token == "Z" {
+        return Some(FixedOffset::east(0));
+    }
+    let sign = match token.chars().nth(0) {
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return None,
+    };
+    let digits: String = token[1..].chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_digit(10)) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
 }
 
 impl Ord for DunePost {
     fn cmp(&self, other: &Self) -> Ordering {
+        // Posts from the same time are ordered by identifier, so that the order is stable
         self.released.timestamp().cmp(&other.released.timestamp())
+            .then_with(|| self.identifier.cmp(&other.identifier))
     }
 }
 
 impl PartialOrd for DunePost {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.released.timestamp().cmp(&other.released.timestamp()))
+        Some(self.cmp(other))
     }
 }
 
@@ -58,3 +364,54 @@ impl PartialEq for DunePost {
 }
 
 impl Eq for DunePost {}
+
+#[test]
+fn test_meta_value() {
+    let utc = FixedOffset::east(0);
+    assert_eq!(DuneMetaValue::new("tags", " swift  rust", &utc),
+               DuneMetaValue::List(vec!["swift".to_owned(), "rust".to_owned()]));
+    assert_eq!(DuneMetaValue::new("inactive", "true", &utc), DuneMetaValue::Bool(true));
+    assert_eq!(DuneMetaValue::new("date", "<2018-04-02 Mon>", &utc),
+               DuneMetaValue::Date(DunePostTime::new(2018, 4, 2, None, &utc).unwrap()));
+    assert_eq!(DuneMetaValue::new("summary", "2018-04-02 was a Monday", &utc).to_string(), "2018-04-02 was a Monday");
+    assert_eq!(DuneMetaValue::new("date", "2018-13-02", &utc), DuneMetaValue::Text("2018-13-02".to_owned()));
+}
+
+#[test]
+fn test_post_time() {
+    let berlin = FixedOffset::east(3600);
+    let time = DunePostTime::parse("<2018-04-12 Thu 10:30>", &berlin).unwrap();
+    assert_eq!(time.rfc3339(), "2018-04-12T10:30:00+01:00");
+    assert_eq!(time.rfc2822(), "Thu, 12 Apr 2018 10:30:00 +0100");
+    assert_eq!(time.localized("de"), "12. April 2018");
+    assert_eq!(time.localized("en"), "April 12, 2018");
+    let octopress = DunePostTime::parse("2011-12-01 19:32:00 +0000", &berlin).unwrap();
+    assert_eq!(octopress.format("%H:%M %z"), "19:32 +0000");
+    assert_eq!(DunePostTime::parse("2018-04-02T10:30:00Z", &berlin).unwrap().timestamp(), 1522665000);
+    assert!(DunePostTime::new(2018, 2, 30, None, &berlin).is_none());
+}
+
+#[test]
+fn test_excerpts() {
+    let post = |contents: &str| DunePost::test_post("2018-01-10-excerpts", &[], contents);
+    // The head of the text, cut between words and never inside of `ö` or `ß`
+    let umlauts = post("<p>Übergrößenträger sind schön und groß</p>");
+    assert_eq!(umlauts.shortened_contents(20), "Übergrößenträger");
+    assert_eq!(umlauts.shortened_contents(21), "Übergrößenträger sind");
+    assert_eq!(umlauts.shortened_contents(5), "Überg");
+    assert_eq!(umlauts.shortened_contents(100), "Übergrößenträger sind schön und groß");
+
+    let marked = post("<p>Intro <em>text</em></p>\n<!--more-->\n<p>Rest of it</p>");
+    assert!(marked.has_more());
+    assert_eq!(marked.excerpt(1), "Intro text");
+    assert!(marked.excerpt_html(1).starts_with("<p>Intro <em>text</em></p>"));
+    assert!(!marked.excerpt_html(1).contains("Rest"));
+
+    let unmarked = post("<p>One <em>two three</em> four</p>");
+    assert!(!unmarked.has_more());
+    assert!(unmarked.excerpt(2).starts_with("One two"));
+    assert!(!unmarked.excerpt(2).contains("three"));
+    let html = unmarked.excerpt_html(2);
+    assert!(html.starts_with("<p>One <em>two"));
+    assert!(html.ends_with("</em></p>"));
+}
@@ -1,5 +1,8 @@
 use traits::*;
 use dune_post::DunePost;
+use dune_page::DuneStaticPage;
+use dune_base::{DuneAuthor, DuneProject};
+use taxonomy::DuneCategory;
 
 pub trait DuneRouter {
     fn post(post: &DunePost) -> String;
@@ -12,6 +15,41 @@ pub trait DuneRouter {
     fn index_pagename<PathBuilder: DunePathBuilder>(builder: &PathBuilder) -> String;
     fn post_pagename<PathBuilder: DunePathBuilder>(builder: &PathBuilder, post: &DunePost) -> String;
 
+    /// The url of a static page, `/about/` for `pages/about.org`
+    fn static_page(page: &DuneStaticPage) -> String {
+        format!("/{}/", page.identifier)
+    }
+
+    /// The prefix of the urls of a language, `/de` for German posts
+    fn language(language: &str) -> String {
+        format!("/{}", language)
+    }
+
+    /// The url of the index page of a series, by the identifier of the series
+    fn series(series: &str) -> String {
+        format!("/series/{}/", series)
+    }
+
+    /// The url of the posts of a category
+    fn category(category: &DuneCategory) -> String {
+        format!("/categories/{}/", category.identifier)
+    }
+
+    /// The url of the projects overview
+    fn projects() -> String {
+        "/projects/".to_owned()
+    }
+
+    /// The url of a project, `/projects/wanderduene/` for `projects/wanderduene.org`
+    fn project(project: &DuneProject) -> String {
+        format!("/projects/{}/", project.identifier)
+    }
+
+    /// The url of the profile of an author, `/authors/terhechte/`
+    fn author(author: &DuneAuthor) -> String {
+        format!("/authors/{}/", author.identifier)
+    }
+
     fn is_overview<PathBuilder: DunePathBuilder>(builder: &PathBuilder, overview: bool) -> String {
         match overview {
             true => Self::overview_pagename(builder),
@@ -19,3 +57,13 @@ pub trait DuneRouter {
         }
     }
 }
+
+/// The url of a post. Posts that are not in the default language
+/// of the blog get the prefix of their language.
+pub fn post_url<Router: DuneRouter>(post: &DunePost, default_language: &str) -> String {
+    match post.language {
+        Some(ref language) if language != default_language =>
+            format!("{}{}", Router::language(language).trim_right_matches('/'), Router::post(post)),
+        _ => Router::post(post)
+    }
+}
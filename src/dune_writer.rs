@@ -5,8 +5,10 @@ use std::fmt;
 use std::io::prelude::*;
 
 use dune_post::DunePost;
-use dune_base::DuneBase;
+use dune_page::DuneStaticPage;
+use dune_base::{DuneAuthor, DuneBase, DuneProject};
 use dune_router::DuneRouter;
+use tangle::DuneTangle;
 
 #[derive(Debug, Clone)]
 pub struct DunePagination {
@@ -48,13 +50,50 @@ pub enum DuneAction {
     Post(PathBuf, Option<DunePagination>, String, DunePost),
     /// Path, Paginationi, Title, Posts, Overview?
     List(PathBuf, Option<DunePagination>, String, Vec<DunePost>, bool),
+    /// Path, Page
+    Page(PathBuf, DuneStaticPage),
+    /// Path, Projects
+    Projects(PathBuf, Vec<DuneProject>),
+    /// Path, Project
+    Project(PathBuf, DuneProject),
+    /// Path, Author
+    Author(PathBuf, DuneAuthor),
+    /// Path, Title, Language, Posts
+    Feed(PathBuf, String, String, Vec<DunePost>),
+    /// Path, Target Url
+    Redirect(PathBuf, String),
+    /// Path (folder or zip file), Mode, Files (Path, Contents)
+    Tangle(PathBuf, DuneTangle, Vec<(String, String)>),
+}
+
+impl DuneAction {
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            &DuneAction::Post(ref path, _, _, _) => path,
+            &DuneAction::List(ref path, _, _, _, _) => path,
+            &DuneAction::Page(ref path, _) => path,
+            &DuneAction::Projects(ref path, _) => path,
+            &DuneAction::Project(ref path, _) => path,
+            &DuneAction::Author(ref path, _) => path,
+            &DuneAction::Feed(ref path, _, _, _) => path,
+            &DuneAction::Redirect(ref path, _) => path,
+            &DuneAction::Tangle(ref path, _, _) => path,
+        }
+    }
 }
 
 impl fmt::Display for DuneAction {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), ::std::fmt::Error> {
         match self {
             &DuneAction::Post(ref path, _, _, _) => write!(f, "{:?}", &path),
-            &DuneAction::List(ref path, _, _, _, _) => write!(f, "{:?}", &path)
+            &DuneAction::List(ref path, _, _, _, _) => write!(f, "{:?}", &path),
+            &DuneAction::Page(ref path, _) => write!(f, "{:?}", &path),
+            &DuneAction::Projects(ref path, ref projects) => write!(f, "{:?} ({} projects)", &path, projects.len()),
+            &DuneAction::Project(ref path, _) => write!(f, "{:?}", &path),
+            &DuneAction::Author(ref path, ref author) => write!(f, "{:?} ({})", &path, author.name),
+            &DuneAction::Feed(ref path, _, ref language, _) => write!(f, "{:?} ({})", &path, &language),
+            &DuneAction::Redirect(ref path, ref target) => write!(f, "{:?} -> {}", &path, &target),
+            &DuneAction::Tangle(ref path, _, ref files) => write!(f, "{:?} ({} files)", &path, files.len())
         };
         Ok(())
     }
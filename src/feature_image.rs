@@ -0,0 +1,76 @@
+use dune_diagnostic::DuneParseResult;
+use org_parser::org_error::{OrgError, OrgErrorKind};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Find the feature image of every post. `#+feature_image:` names a file in
+/// the content folder (or a full url), otherwise the image is found by name:
+/// `<identifier>-feature-image.jpg`. Only a `#+feature_image:` that doesn't
+/// exist gets a warning, most posts don't have an image.
+pub fn associate(result: &mut DuneParseResult, content_folder: &Path, content_url: &str) {
+    let mut files: Vec<String> = match fs::read_dir(content_folder) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    let content_url = content_url.trim_right_matches('/');
+
+    let mut warnings: Vec<(PathBuf, OrgError)> = Vec::new();
+    for post in result.posts.iter_mut() {
+        let reference = post.meta("feature_image");
+        if !reference.is_empty() {
+            if reference.starts_with("http://") || reference.starts_with("https://") {
+                post.feature_image = Some(reference);
+                continue;
+            }
+            // `/content/image.jpg` and `image.jpg` are the same file, `/contentfoo.jpg` isn't
+            let in_content_url = reference.len() > content_url.len()
+                && reference.starts_with(content_url)
+                && reference[content_url.len()..].starts_with('/');
+            let name = match in_content_url {
+                true => &reference[content_url.len()..],
+                false => &reference[..],
+            }.trim_left_matches('/').to_owned();
+            if content_folder.join(&name).is_file() {
+                post.feature_image = Some(format!("{}/{}", content_url, name));
+            } else {
+                warnings.push((post.source.clone(), OrgError::new(
+                    OrgErrorKind::MissingAsset,
+                    format!("The feature image {} is not in {}", reference, content_folder.display()))));
+            }
+            continue;
+        }
+        let prefix = format!("{}-feature-image.", post.identifier);
+        if let Some(file) = files.iter().find(|file| file.starts_with(&prefix)) {
+            post.feature_image = Some(format!("{}/{}", content_url, file));
+        }
+    }
+    for (path, warning) in warnings {
+        result.warning(path, warning);
+    }
+}
+
+#[test]
+fn test_associate() {
+    use dune_post::DunePost;
+    let folder = ::std::env::temp_dir().join("wanderdune-test-feature-images");
+    fs::create_dir_all(&folder).unwrap();
+    fs::File::create(folder.join("foo.jpg")).unwrap();
+    let post = |name: &str, image: &str| DunePost::test_post(name, &[("feature_image", image)], "");
+    let mut result = DuneParseResult::new();
+    result.posts = vec![post("2018-01-10-cdn", "https://cdn.example.com/a.jpg"),
+                        post("2018-01-11-content", "/content/foo.jpg"),
+                        post("2018-01-12-prefix", "/contentfoo.jpg"),
+                        post("2018-01-13-none", "")];
+    associate(&mut result, &folder, "/content");
+    assert_eq!(result.posts[0].feature_image_url("https://appventure.me/"),
+               Some("https://cdn.example.com/a.jpg".to_owned()));
+    assert_eq!(result.posts[1].feature_image_url("https://appventure.me/"),
+               Some("https://appventure.me/content/foo.jpg".to_owned()));
+    assert_eq!(result.posts[2].feature_image, None);
+    assert_eq!(result.diagnostics.len(), 1);
+}
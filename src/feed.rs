@@ -0,0 +1,80 @@
+use dune_base::DuneAuthor;
+use dune_post::DunePost;
+use utils::escape_html;
+
+/// How many posts a feed has
+pub const FEED_LENGTH: usize = 20;
+
+/// The Atom feed of the posts, newest first. `base_url` makes all
+/// links absolute, `post_url` is the url of a post on the blog.
+/// `authors` has the names of the `#+author:` identifiers.
+pub fn atom<F>(title: &str, base_url: &str, feed_url: &str, language: &str,
+               posts: &[DunePost], authors: &[DuneAuthor], post_url: F) -> String
+where
+    F: Fn(&DunePost) -> String
+{
+    let base_url = base_url.trim_right_matches('/');
+    let mut posts: Vec<&DunePost> = posts.iter().filter(|post| post.is_listed()).collect();
+    posts.sort();
+    posts.reverse();
+    posts.truncate(FEED_LENGTH);
+
+    let mut feed = String::new();
+    feed.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    feed.push_str(&format!("<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"{}\">\n", escape_html(language)));
+    feed.push_str(&format!("  <title>{}</title>\n", escape_html(title)));
+    feed.push_str(&format!("  <link href=\"{}{}\" rel=\"self\"/>\n", base_url, escape_html(feed_url)));
+    feed.push_str(&format!("  <link href=\"{}/\"/>\n", base_url));
+    feed.push_str(&format!("  <id>{}{}</id>\n", base_url, escape_html(feed_url)));
+    if let Some(post) = posts.first() {
+        feed.push_str(&format!("  <updated>{}</updated>\n", post.released.rfc3339()));
+    }
+    for post in posts {
+        let url = format!("{}{}", base_url, escape_html(&post_url(post)));
+        // Feed readers don't know the blog, so `/content/image.jpg` has to be absolute
+        let mut contents = post.contents
+            .replace("src=\"/", &format!("src=\"{}/", base_url))
+            .replace("href=\"/", &format!("href=\"{}/", base_url));
+        if let Some(image) = post.feature_image_url(base_url) {
+            contents = format!("<p><img src=\"{}\" alt=\"\"/></p>\n{}", escape_html(&image), contents);
+        }
+        feed.push_str("  <entry>\n");
+        feed.push_str(&format!("    <title>{}</title>\n", escape_html(&post.title)));
+        feed.push_str(&format!("    <link href=\"{}\"/>\n", url));
+        feed.push_str(&format!("    <id>{}</id>\n", url));
+        feed.push_str(&format!("    <published>{}</published>\n", post.released.rfc3339()));
+        feed.push_str(&format!("    <updated>{}</updated>\n", post.released.rfc3339()));
+        for identifier in &post.authors {
+            match authors.iter().find(|author| author.is_named(identifier)) {
+                Some(author) => {
+                    feed.push_str(&format!("    <author>\n      <name>{}</name>\n", escape_html(&author.name)));
+                    if let Some(ref website) = author.website {
+                        feed.push_str(&format!("      <uri>{}</uri>\n", escape_html(website)));
+                    }
+                    feed.push_str("    </author>\n");
+                }
+                None => feed.push_str(&format!("    <author>\n      <name>{}</name>\n    </author>\n", escape_html(identifier))),
+            }
+        }
+        for tag in &post.tags {
+            feed.push_str(&format!("    <category term=\"{}\"/>\n", escape_html(tag)));
+        }
+        if !post.description.is_empty() {
+            feed.push_str(&format!("    <summary>{}</summary>\n", escape_html(&post.description)));
+        }
+        feed.push_str(&format!("    <content type=\"html\">{}</content>\n", escape_html(&contents)));
+        feed.push_str("  </entry>\n");
+    }
+    feed.push_str("</feed>\n");
+    feed
+}
+
+#[test]
+fn test_atom() {
+    let post = DunePost::test_post("2018-01-10-tuples", &[("title", "Tuples & Enums")], "<img src=\"/content/tuple.png\"/>");
+    let feed = atom("Appventure", "https://appventure.me/", "/feed.xml", "en", &[post], &[],
+                    |post| format!("/{}", post.identifier));
+    assert!(feed.contains("<title>Tuples &amp; Enums</title>"));
+    assert!(feed.contains("<link href=\"https://appventure.me/2018-01-10-tuples\"/>"));
+    assert!(feed.contains("src=&quot;https://appventure.me/content/tuple.png&quot;"));
+}
@@ -1,19 +1,33 @@
 use std::error::Error;
 use std::io;
-use std::path::{PathBuf, Path};
-use std::collections::HashMap;
+use std::path::{Component, PathBuf, Path};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::rc::Rc;
+use std::sync::Arc;
 use std::cell::Cell;
 use std::marker;
 
 use configuration::Configuration;
 use dune_post::DunePost;
+use dune_page::DuneStaticPage;
+use dune_diagnostic::{DuneDiagnostic, DuneParseResult, DuneSeverity};
+use feature_image;
+use org_parser::org_error::{OrgError, OrgErrorKind};
+use tangle::{self, DuneTangle};
 use org_parser::OrgParser;
+use org_parser::cache_db::CacheDB;
+use markdown_parser::MarkdownParser;
+use page_parser::PageParser;
+use project_parser::ProjectParser;
+use author_parser::{self, AuthorParser};
 use dune_writer::*;
 use html_writer::*;
 use traits::*;
-use dune_router::DuneRouter;
+use dune_router::{self, DuneRouter};
 use dune_base::*;
+use dune_index::DuneIndex;
+use related;
+use utils::slugify;
 
 
 trait PathAppending {
@@ -30,30 +44,120 @@ impl PathAppending for PathBuf {
 
 #[derive(Copy, Clone)]
 pub enum DuneBaseAggType {
-    Year, Month, Day, Tag, Keyword, Enabled
+    Year, Month, Day, Tag, Keyword, Enabled, Series, Category
 }
 
-struct Dune {
+/// A build of the blog: the parsed posts and everything the builders
+/// wrote into it. `execute` renders all of it with a `DuneWriter`.
+pub struct Dune {
     database: Rc<DuneBase>,
-    receiver: Rc<ActionReceiver>
+    receiver: Rc<ActionReceiver>,
+    diagnostics: Vec<DuneDiagnostic>
 }
 
 impl Dune {
-    fn new(configuration: Rc<Configuration>, posts: Vec<DunePost>, projects: Vec<DuneProject>) -> Dune {
-        let tags = Dune::aggregate(&posts, |post| &post.tags);
-        let keywords = Dune::aggregate(&posts, |post| &post.keywords);
+    /// Parse the posts, pages, projects and authors from the folders of the
+    /// configuration. With `strict`, any post that could not be built is an error.
+    pub fn load(configuration: Rc<Configuration>) -> Result<Dune, Box<Error>> {
+        let cache = Arc::new(CacheDB::open(configuration.cache_file())?);
+        let dune = {
+            let timezone = configuration.timezone();
+            let parser = OrgParser::new(configuration.post_folder(), configuration.max_threads(), Arc::clone(&cache))
+                .timezone(timezone);
+            let markdown_parser = MarkdownParser::new(configuration.post_folder(), Arc::clone(&cache))
+                .timezone(timezone);
+            let page_parser = PageParser::new(configuration.page_folder()).timezone(timezone);
+            let project_parser = ProjectParser::new(configuration.project_folder());
+            let author_parser = AuthorParser::new(configuration.author_folder());
+            Dune::new(Rc::clone(&configuration),
+                      &[&parser, &markdown_parser, &page_parser, &project_parser, &author_parser])
+        };
+        cache.flush()?;
+        let errors = dune.diagnostics.iter()
+            .filter(|diagnostic| diagnostic.severity == DuneSeverity::Error)
+            .count();
+        if configuration.strict() && errors > 0 {
+            return Err(From::from(format!("{} posts could not be built", errors)));
+        }
+        Ok(dune)
+    }
+
+    /// A build of the posts of `sources`, i.e. for blogs with other folders
+    pub fn new(configuration: Rc<Configuration>, sources: &[&DunePostSource]) -> Dune {
+        let mut result = DuneParseResult::new();
+        for source in sources {
+            result.extend(source.parse());
+        }
+        feature_image::associate(&mut result, configuration.content_folder(), configuration.content_url());
+        author_parser::check_authors(&mut result);
+        // `iOS` and `ios` are the same tag
+        let taxonomy = configuration.taxonomy();
+        for post in result.posts.iter_mut() {
+            post.tags = taxonomy.normalize_all(&post.tags);
+        }
+        for project in result.projects.iter_mut() {
+            project.tags = taxonomy.normalize_all(&project.tags);
+        }
+        // `#+alias: ../../etc/x` would be written outside of the html folder
+        let mut rejected: Vec<(PathBuf, String)> = Vec::new();
+        for post in result.posts.iter_mut() {
+            let (inside, outside): (Vec<String>, Vec<String>) = post.aliases.drain(..)
+                .partition(|alias| is_inside(alias));
+            post.aliases = inside;
+            rejected.extend(outside.into_iter().map(|alias| (post.source.clone(), alias)));
+        }
+        for (path, alias) in rejected {
+            result.warning(path, OrgError::new(
+                OrgErrorKind::MalformedMetadata,
+                format!("The alias {} is outside of the blog, skipping it", alias)));
+        }
+        for diagnostic in &result.diagnostics {
+            println!("{}", diagnostic);
+        }
+        let include_drafts = configuration.include_drafts();
+        let posts: Vec<DunePost> = result.posts.into_iter()
+            .filter(|post| include_drafts || !post.is_draft())
+            .collect();
+        let pages: Vec<DuneStaticPage> = result.pages.into_iter()
+            .filter(|page| include_drafts || page.enabled)
+            .collect();
+        let listed: Vec<DunePost> = posts.iter().filter(|post| post.is_listed()).cloned().collect();
+        let tags = Dune::aggregate(&listed, |post| &post.tags);
+        let keywords = Dune::aggregate(&listed, |post| &post.keywords);
+        let series = Dune::series(&listed);
+        let index = DuneIndex::new(&posts);
+        // `Blog` excludes the tag `blog`
+        let excluded = taxonomy.normalize_all(configuration.related_excluded_tags());
+        let related = related::related(&posts, configuration.related_count(), &excluded,
+                                       configuration.language());
         Dune {
             database: Rc::new(DuneBase {
                 posts: posts,
-                projects: projects,
+                pages: pages,
+                projects: result.projects,
+                authors: result.authors,
                 tags: tags,
                 keywords: keywords,
+                series: series,
+                index: index,
+                related: related,
+                taxonomy: taxonomy,
                 configuration: configuration
             }),
-            receiver: Rc::new(ActionReceiver::new())
+            receiver: Rc::new(ActionReceiver::new()),
+            diagnostics: result.diagnostics
         }
     }
 
+    pub fn database(&self) -> &Rc<DuneBase> {
+        &self.database
+    }
+
+    /// Everything that went wrong while parsing, these are printed as well
+    pub fn diagnostics(&self) -> &[DuneDiagnostic] {
+        &self.diagnostics
+    }
+
     fn aggregate<A>(posts: &[DunePost], with_parser: A) -> Vec<DuneGroup>
     where
         A: Fn(&DunePost) -> &[String]
@@ -77,35 +181,132 @@ impl Dune {
         return result;
     }
 
-    fn builder(&self) -> Builder {
+    /// Group the posts by `#+series:`, every series in order
+    fn series(posts: &[DunePost]) -> Vec<DuneSeries> {
+        let mut grouped: BTreeMap<String, Vec<&DunePost>> = BTreeMap::new();
+        for post in posts {
+            if let Some(ref name) = post.series {
+                grouped.entry(slugify(name)).or_insert(Vec::new()).push(post);
+            }
+        }
+        grouped.into_iter().map(|(identifier, mut posts)| {
+            posts.sort_by_key(|post| series_order(post));
+            DuneSeries {
+                identifier: identifier,
+                title: posts[0].series.clone().unwrap_or_default(),
+                posts: posts.iter().map(|post| post.identifier.clone()).collect()
+            }
+        }).collect()
+    }
+
+    /// A builder for the posts in the language of the blog
+    pub fn builder(&self) -> Builder {
         let path = PathBuf::from(self.database.configuration.html_folder());
-        let posts: Vec<&DunePost> = self.database.posts.iter().collect();
+        let language = self.database.configuration.language();
+        let posts: Vec<&DunePost> = self.database.posts.iter()
+            .filter(|post| self.database.language_of(post) == language)
+            .collect();
         Builder::new(Rc::clone(&self.database), path, posts, Rc::clone(&self.receiver))
     }
 
-    // FIXME: Maybe move this into the database?
-    fn post_by_identifier<'a>(&self, identifier: &str) -> &'a DunePost {
-        panic!();
+    /// The languages of the translated posts, without the language of the blog
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = self.database.posts.iter()
+            .map(|post| self.database.language_of(post).to_owned())
+            .filter(|language| language != self.database.configuration.language())
+            .collect();
+        languages.sort();
+        languages.dedup();
+        languages
     }
 
-    fn execute<Writer: DuneWriter, Router: DuneRouter>(&self, writer: &Writer, router: &Router) {
-        for action in self.receiver.actions() {
+    /// A builder for the posts in another language, in the folder
+    /// of the language prefix of the router
+    pub fn language_builder<Router: DuneRouter>(&self, language: &str, router: &Router) -> Builder {
+        let mut path = PathBuf::from(self.database.configuration.html_folder());
+        path.push(Router::language(language).trim_matches('/'));
+        let posts: Vec<&DunePost> = self.database.posts.iter()
+            .filter(|post| self.database.language_of(post) == language)
+            .collect();
+        Builder::new(Rc::clone(&self.database), path, posts, Rc::clone(&self.receiver))
+    }
+
+    pub fn post_by_identifier(&self, identifier: &str) -> Option<&DunePost> {
+        self.database.post(identifier)
+    }
+
+    /// Write everything the builders collected, stops at the first error
+    pub fn execute<Writer: DuneWriter, Router: DuneRouter>(&self, writer: &Writer, router: &Router) -> io::Result<()> {
+        for action in Dune::check_routes(self.receiver.actions()) {
             println!("Writing: {}", &action);
-            writer.write(&self.database, &action, router);
+            writer.write(&self.database, &action, router)?;
         }
+        Ok(())
+    }
+
+    /// Aliases must not replace real pages or each other.
+    /// Colliding redirects are reported and skipped.
+    fn check_routes(actions: Vec<DuneAction>) -> Vec<DuneAction> {
+        let routes: HashSet<PathBuf> = actions.iter()
+            .filter(|action| match action {
+                &&DuneAction::Redirect(_, _) => false,
+                _ => true
+            })
+            .map(|action| action.path().clone())
+            .collect();
+        let mut redirects: HashSet<PathBuf> = HashSet::new();
+        actions.into_iter().filter(|action| match action {
+            &DuneAction::Redirect(ref path, ref target) => {
+                if routes.contains(path) {
+                    println!("Alias {:?} for {} collides with a page, skipping it", path, target);
+                    false
+                } else if !redirects.insert(path.clone()) {
+                    println!("Alias {:?} for {} is used more than once, skipping it", path, target);
+                    false
+                } else {
+                    true
+                }
+            }
+            _ => true
+        }).collect()
     }
 }
 
+/// Parts without a number come after the numbered ones, by date
+fn series_order(post: &DunePost) -> (i32, i64) {
+    (post.series_part.unwrap_or(i32::max_value()), post.released.timestamp())
+}
+
+/// The file for a url: `/2011/12/index.html` stays as it is,
+/// `/old-post/` and `/old-post` become `/old-post/index.html`
+fn route_path(root: &Path, alias: &str) -> PathBuf {
+    let mut path = root.to_path_buf();
+    path.push(alias.trim_left_matches('/'));
+    if alias.ends_with('/') || path.extension().is_none() {
+        path.push("index.html");
+    }
+    path
+}
+
+/// Whether the url stays inside of the html folder, `/old/post/`
+/// does, `/../etc/x` or `/old/./post` don't
+fn is_inside(url: &str) -> bool {
+    Path::new(url.trim_left_matches('/')).components().all(|component| match component {
+        Component::Normal(_) => true,
+        _ => false
+    })
+}
+
 // Traits
 
 
 
-trait DuneBuildMapper<'a> {
+pub trait DuneBuildMapper<'a> {
     fn group_by(self, key: DuneBaseAggType) -> GroupedDuneBuilder<'a>;
     fn paged(self, i32, &str) -> PagedDuneBuilder<'a>;
 }
 
-trait DuneBuildFlatter<'a> where Self::BuilderType: DuneBuildWriter<'a> {
+pub trait DuneBuildFlatter<'a> where Self::BuilderType: DuneBuildWriter<'a> {
     type CategoryType;
     type BuilderType;
     /// Will iterate over the contents of this collection. For each entry, a new
@@ -115,13 +316,13 @@ trait DuneBuildFlatter<'a> where Self::BuilderType: DuneBuildWriter<'a> {
     fn with<F>(self, action: F) -> Self where F: (Fn(Self::BuilderType, Self::CategoryType) -> ());
 }
 
-trait DuneBuilder {
+pub trait DuneBuilder {
     fn path(&self) -> &PathBuf;
     fn database(&self) -> &Rc<DuneBase>;
     fn parent(&self) -> &Rc<ActionReceiver>;
 }
 
-trait DuneBuildCollector<'a> {
+pub trait DuneBuildCollector<'a> {
     fn receive(self, action: DuneAction) -> Self;
 
     fn collected(&self) -> Vec<&'a DunePost>;
@@ -129,6 +330,32 @@ trait DuneBuildCollector<'a> {
         self.collected().into_iter().map(|post|post.clone()).collect()
     }
 
+    /// Write a redirect to the post for each alias of the collected posts
+    fn write_aliases<Router: DuneRouter>(self, router: &Router) -> Self where Self: marker::Sized + DuneBuilder {
+        let root_path = PathBuf::from(self.database().configuration.html_folder());
+        let language = self.database().configuration.language().to_owned();
+        let mut actions: Vec<DuneAction> = Vec::new();
+        for post in self.collected() {
+            for alias in post.aliases.iter().filter(|alias| is_inside(alias)) {
+                actions.push(DuneAction::Redirect(route_path(&root_path, alias),
+                                                  dune_router::post_url::<Router>(post, &language)));
+            }
+        }
+        actions.into_iter().fold(self, |builder, action| builder.receive(action))
+    }
+
+    /// Write an Atom feed of the collected posts into `feed.xml` at the
+    /// current path, in the language of the posts
+    fn write_feed<Router: DuneRouter>(self, router: &Router, title: String) -> Self where Self: marker::Sized + DuneBuilder {
+        let path = self.path().appending("feed.xml");
+        let posts = self.into_collected();
+        let language = match posts.first() {
+            Some(post) => self.database().language_of(post).to_owned(),
+            None => self.database().configuration.language().to_owned()
+        };
+        self.receive(DuneAction::Feed(path, title, language, posts))
+    }
+
     fn with_posts<F>(self, action: F) -> Self where F: (Fn(PostBuilder<'a>) -> ()), Self: marker::Sized + DuneBuilder {
         let count = self.collected().len() as i32;
         let collected = self.collected();
@@ -146,18 +373,21 @@ trait DuneBuildCollector<'a> {
     }
 }
 
-trait DuneBuildWriter<'a> {
+pub trait DuneBuildWriter<'a> {
     fn write<Router: DuneRouter>(self, router: &Router, title: String, overview: bool) -> Self
     where Self: marker:: Sized + DuneBuilder + DuneBuildCollector<'a> + DunePathBuilder
     {
         let path = self.path().appending(&Router::is_overview(&self, overview));
         let mut posts = self.into_collected();
+        // Unlisted posts have their own page, but are not in any list
+        posts.retain(|post| post.is_listed());
         self.receive(DuneAction::List(path, None, title, posts, true))
     }
 
     fn clone_to<T: AsRef<Path>>(self, path: T, title: String, overview: bool) -> Self
     where Self: marker::Sized + DuneBuilder + DuneBuildCollector<'a> + DunePathBuilder {
         let mut posts = self.into_collected();
+        posts.retain(|post| post.is_listed());
         let mut root_path = PathBuf::from(self.database().configuration.html_folder());
         root_path.push(path);
         self.receive(DuneAction::List(root_path, None, title, posts, overview))
@@ -166,7 +396,8 @@ trait DuneBuildWriter<'a> {
 
 // Types
 
-struct ActionReceiver {
+/// Collects the actions of all builders of a `Dune`
+pub struct ActionReceiver {
     actions: Cell<Option<Vec<DuneAction>>>,
 }
 
@@ -194,7 +425,7 @@ impl ActionReceiver {
     }
 }
 
-struct Builder<'a> {
+pub struct Builder<'a> {
     payload: Vec<&'a DunePost>,
     path: PathBuf,
     database: Rc<DuneBase>,
@@ -217,16 +448,60 @@ impl<'a> Builder<'a> {
         }
     }
 
-    fn reversed(mut self) -> Self {
+    pub fn reversed(mut self) -> Self {
         self.payload.reverse();
         self
     }
 
-    fn sorted(mut self) -> Self {
+    pub fn sorted(mut self) -> Self {
         self.payload.sort();
         self
     }
 
+    /// Keep the posts that are also in the result of a query on the
+    /// database, i.e. `builder.query(|base| base.posts_in_year(2018))`
+    pub fn query<F>(mut self, query: F) -> Self where F: Fn(&DuneBase) -> Vec<&DunePost> {
+        let identifiers: HashSet<String> = query(&self.database).into_iter()
+            .map(|post| post.identifier.clone())
+            .collect();
+        self.payload.retain(|post| identifiers.contains(&post.identifier));
+        self
+    }
+
+    /// Write every static page to its own route. Pages are not part
+    /// of the collected posts, so they don't show up in any list.
+    pub fn write_pages<Router: DuneRouter>(self, router: &Router) -> Self {
+        let root_path = PathBuf::from(self.database.configuration.html_folder());
+        let actions: Vec<DuneAction> = self.database.pages.iter()
+            .map(|page| DuneAction::Page(route_path(&root_path, &Router::static_page(page)), page.clone()))
+            .collect();
+        actions.into_iter().fold(self, |builder, action| builder.receive(action))
+    }
+
+    /// Write the projects overview and a page for every project
+    pub fn write_projects<Router: DuneRouter>(self, router: &Router) -> Self {
+        if self.database.projects.is_empty() {
+            return self;
+        }
+        let root_path = PathBuf::from(self.database.configuration.html_folder());
+        let mut actions: Vec<DuneAction> = vec![
+            DuneAction::Projects(route_path(&root_path, &Router::projects()), self.database.projects.clone())
+        ];
+        for project in &self.database.projects {
+            actions.push(DuneAction::Project(route_path(&root_path, &Router::project(project)), project.clone()));
+        }
+        actions.into_iter().fold(self, |builder, action| builder.receive(action))
+    }
+
+    /// Write a profile page for every author
+    pub fn write_authors<Router: DuneRouter>(self, router: &Router) -> Self {
+        let root_path = PathBuf::from(self.database.configuration.html_folder());
+        let actions: Vec<DuneAction> = self.database.authors.iter()
+            .map(|author| DuneAction::Author(route_path(&root_path, &Router::author(author)), author.clone()))
+            .collect();
+        actions.into_iter().fold(self, |builder, action| builder.receive(action))
+    }
+
 }
 
 impl<'a> DuneBuildMapper<'a> for Builder<'a> {
@@ -240,7 +515,9 @@ impl<'a> DuneBuildMapper<'a> for Builder<'a> {
                     DuneBaseAggType::Day => vec![elm.released.day.to_string()],
                     DuneBaseAggType::Tag => elm.tags.clone(),
                     DuneBaseAggType::Keyword => elm.keywords.clone(),
-                    DuneBaseAggType::Enabled => vec![format!("{}", elm.enabled)]
+                    DuneBaseAggType::Enabled => vec![format!("{}", elm.enabled)],
+                    DuneBaseAggType::Series => elm.series.iter().map(|name| slugify(name)).collect(),
+                    DuneBaseAggType::Category => self.database.taxonomy.categories_for(&elm.tags)
                 };
                 for key in keys {
                     let mut entry = acc.entry(key).or_insert(
@@ -253,16 +530,22 @@ impl<'a> DuneBuildMapper<'a> for Builder<'a> {
         });
         // FIXME: Can I map this?
         let mut payload: Vec<(String, Vec<&'a DunePost>)> = Vec::new();
-        for (key, posts) in grouped {
-            payload.push((key, posts));
+        for (name, mut posts) in grouped {
+            // A series is read in order, not by date
+            if let DuneBaseAggType::Series = key {
+                posts.sort_by_key(|post| series_order(post));
+            }
+            payload.push((name, posts));
         }
         let builder = GroupedDuneBuilder::new(Rc::clone(&self.database), self.path.clone(), payload, Rc::clone(&self.parent));
         builder
     }
 
-    fn paged(self, per_page: i32, in_path: &str) -> PagedDuneBuilder<'a> {
+    fn paged(mut self, per_page: i32, in_path: &str) -> PagedDuneBuilder<'a> {
         let mut result: Vec<DunePage> = Vec::new();
         let mut counter: i32 = 0;
+        // Pages are lists, so unlisted posts are not part of them
+        self.payload.retain(|post| post.is_listed());
         loop {
             let cloned = self.payload.clone();
             let entries: Vec<&DunePost> = cloned
@@ -317,7 +600,7 @@ impl<'a> DunePathBuilder for Builder<'a> {
 
 impl<'a> DuneBuildWriter<'a> for Builder<'a> {}
 
-struct PostBuilder<'a> {
+pub struct PostBuilder<'a> {
     payload: Vec<&'a DunePost>,
     index: usize,
     path: PathBuf,
@@ -332,12 +615,12 @@ impl<'a> DuneBuilder for PostBuilder<'a> {
 }
 
 impl<'a> PostBuilder<'a> {
-    fn post(&self) -> &'a DunePost {
+    pub fn post(&self) -> &'a DunePost {
         self.payload[self.index]
     }
 
     /// Write the post at the current path using the given filename
-    fn write_post<Router: DuneRouter>(self, router: &Router, title: String) -> Self {
+    pub fn write_post<Router: DuneRouter>(self, router: &Router, title: String) -> Self {
         let post = self.payload[self.index];
         let path = self.path.appending(&Router::post_pagename(&self, &post));
         let next = if self.index < (self.payload.len() - 1) { Some(self.index + 1) } else { None };
@@ -351,6 +634,27 @@ impl<'a> PostBuilder<'a> {
         let action = DuneAction::Post(path, Some(pagination), title, post.clone());
         self.receive(action)
     }
+
+    /// Write the `:tangle` source blocks of the post into the current
+    /// folder, or as `<identifier>.zip` next to the post
+    pub fn write_tangled(self, mode: DuneTangle) -> Self {
+        let post = self.payload[self.index];
+        let files = tangle::snippets(&post.identifier, &post.source)
+            .and_then(|snippets| tangle::tangled_files(&snippets));
+        let files = match files {
+            Ok(ref files) if files.is_empty() => return self,
+            Ok(files) => files,
+            Err(e) => {
+                println!("Could not tangle {:?}: {}", &post.source, e);
+                return self;
+            }
+        };
+        let path = match mode {
+            DuneTangle::Files => self.path.clone(),
+            DuneTangle::Zip => self.path.appending(&format!("{}.zip", post.identifier)),
+        };
+        self.receive(DuneAction::Tangle(path, mode, files))
+    }
 }
 
 impl<'a> DunePathBuilder for PostBuilder<'a> {
@@ -378,7 +682,7 @@ impl<'a> DuneBuildCollector<'a> for PostBuilder<'a> {
 
 
 
-struct GroupedDuneBuilder<'a> {
+pub struct GroupedDuneBuilder<'a> {
     database: Rc<DuneBase>,
     payload: Vec<(String, Vec<&'a DunePost>)>,
     path: PathBuf,
@@ -448,7 +752,7 @@ struct DunePage<'a> {
     posts: Vec<&'a DunePost>
 }
 
-struct PagedDuneBuilder<'a> {
+pub struct PagedDuneBuilder<'a> {
     database: Rc<DuneBase>,
     payload: Vec<DunePage<'a>>,
     path: PathBuf,
@@ -523,7 +827,7 @@ impl<'a> DunePathBuilder for PagedDuneBuilder<'a> {
     }
 }
 
-struct PageDuneBuilder<'a> {
+pub struct PageDuneBuilder<'a> {
     database: Rc<DuneBase>,
     payload: Vec<DunePage<'a>>,
     index: usize,
@@ -620,6 +924,15 @@ fn testing() {
             //Path::new("/Users/terhechte/Development/Rust/rusttest1/posts")
             Path::new("/home/terhechte/Development/Rust/wanderduene/posts")
         }
+        fn taxonomy(&self) -> ::taxonomy::DuneTaxonomy {
+            ::taxonomy::DuneTaxonomy::new()
+                .alias("objetive-c", "objective-c")
+                .tag("ios", "iOS", "")
+                .tag("macos", "macOS", "")
+                .category("swift", "Swift Topics", None, &["swift"])
+                .category("language", "Language Features", Some("swift"), &["generics", "enums"])
+                .category("apple", "Apple", None, &["ios", "macos", "cocoa", "objective-c"])
+        }
     }
 
     let configuration = Rc::new(AppventureConfig {});
@@ -627,10 +940,16 @@ fn testing() {
     // will fail. Putting it into its own line, works fine.
     let cloned = Rc::clone(&configuration);
 
-    let parser = OrgParser::new(&cloned.post_folder(), 2);
-    let posts = parser.parse();
+    let cache = Arc::new(CacheDB::open(cloned.cache_file()).unwrap());
+    let parser = OrgParser::new(&cloned.post_folder(), 2, Arc::clone(&cache));
+    let markdown_parser = MarkdownParser::new(&cloned.post_folder(), Arc::clone(&cache));
+
+    let page_parser = PageParser::new(&cloned.page_folder());
+    let project_parser = ProjectParser::new(&cloned.project_folder());
+    let author_parser = AuthorParser::new(&cloned.author_folder());
 
-    let db = Dune::new(cloned, posts, Vec::new());
+    let db = Dune::new(cloned, &[&parser, &markdown_parser, &page_parser, &project_parser, &author_parser]);
+    cache.flush().unwrap();
     let builder = db.builder();
 
     builder.group_by(DuneBaseAggType::Year)
@@ -641,12 +960,24 @@ fn testing() {
                         .with(|builder, day| {
                             builder.with_posts(|postbuilder| {
                                 let title = &postbuilder.post().path;
-                                postbuilder.push(title).write_post(&TestingRouter, title.clone());
+                                postbuilder.push(title)
+                                    .write_post(&TestingRouter, title.clone())
+                                    .write_tangled(DuneTangle::Zip);
                             }).write(&TestingRouter, format!("{} {} {}", year, month, day), true);
                         }).write(&TestingRouter, format!("{} {}", year, month), true);
                 }).write(&TestingRouter, format!("{}", year), true);
         });
 
+    db.builder().push("series")
+        .group_by(DuneBaseAggType::Series)
+        .with(|builder, series| {
+            let title = builder.database().series.iter()
+                .find(|other| other.identifier == series)
+                .map(|other| other.title.clone())
+                .unwrap_or(series);
+            builder.write(&TestingRouter, title, true);
+        });
+
     let builder = db.builder();
     builder.push("tags")
         .group_by(DuneBaseAggType::Tag)
@@ -656,6 +987,41 @@ fn testing() {
         });
 
 
+    db.builder().push("categories")
+        .group_by(DuneBaseAggType::Category)
+        .with(|builder, category| {
+            let name = builder.database().category(&category)
+                .map(|category| category.name.clone())
+                .unwrap_or(category);
+            builder.sorted().reversed().write(&TestingRouter, name, false);
+        });
+
+    db.builder().push("2018")
+        .query(|base| base.posts_in_year(2018))
+        .sorted()
+        .reversed()
+        .write(&TestingRouter, "2018".to_owned(), false);
+
+    db.builder().write_aliases(&TestingRouter);
+    db.builder().write_feed(&TestingRouter, "Appventure.me".to_owned());
+
+    for language in db.languages() {
+        db.language_builder(&language, &TestingRouter)
+            .with_posts(|postbuilder| {
+                let post = postbuilder.post();
+                postbuilder.push(&post.released.year).push(&post.released.month).push(&post.released.day)
+                    .push(&post.path)
+                    .write_post(&TestingRouter, post.title.clone());
+            })
+            .sorted()
+            .reversed()
+            .write(&TestingRouter, format!("Appventure.me {}", language), false)
+            .write_feed(&TestingRouter, format!("Appventure.me {}", language));
+    }
+    db.builder().write_pages(&TestingRouter);
+    db.builder().write_projects(&TestingRouter);
+    db.builder().write_authors(&TestingRouter);
+
     let builder = db.builder();
     builder.push("latest-posts")
         .sorted()
@@ -671,5 +1037,32 @@ fn testing() {
     let cloned = Rc::clone(&configuration);
     let writer = HTMLWriter::new(cloned);
 
-    db.execute(&writer, &TestingRouter);
+    db.execute(&writer, &TestingRouter).unwrap();
+}
+
+#[test]
+fn test_check_routes() {
+    let root = Path::new("html");
+    let actions = vec![
+        DuneAction::List(root.join("2011/12/index.html"), None, "2011 12".to_owned(), Vec::new(), true),
+        DuneAction::Redirect(route_path(root, "/2011/12/index.html"), "/2011/12/01/blogstrapped".to_owned()),
+        DuneAction::Redirect(route_path(root, "/blog/old-post/"), "/2012/01/01/new-post".to_owned()),
+        DuneAction::Redirect(route_path(root, "/blog/old-post"), "/2012/01/01/other-post".to_owned()),
+    ];
+    let checked = Dune::check_routes(actions);
+    assert_eq!(checked.len(), 2);
+    assert_eq!(checked[1].path(), &root.join("blog/old-post/index.html"));
+    assert!(is_inside("/blog/old-post/"));
+    assert!(!is_inside("../../etc/x"));
+    assert!(!is_inside("/blog/../../x"));
+}
+
+#[test]
+fn test_series() {
+    let post = |name: &str, part: &str| DunePost::test_post(name, &[("series", "Pattern Matching"), ("series_part", part)], "");
+    let series = Dune::series(&[post("2015-08-01-patterns-two", "2"), post("2015-07-01-patterns-one", "1"),
+                                post("2015-09-01-patterns-extra", "")]);
+    assert_eq!(series.len(), 1);
+    assert_eq!(series[0].identifier, "pattern-matching");
+    assert_eq!(series[0].posts, vec!["2015-07-01-patterns-one", "2015-08-01-patterns-two", "2015-09-01-patterns-extra"]);
 }
@@ -1,5 +1,6 @@
 use askama::Template;
 
+use std::fmt;
 use std::io;
 use std::fs;
 use std::io::prelude::*;
@@ -7,14 +8,17 @@ use std::rc::Rc;
 use std::path::{Path, PathBuf};
 use std::marker::PhantomData;
 
-// FIXME:
-use std::error::Error;
-
 use configuration::Configuration;
 use dune_writer::*;
-use dune_base::DuneBase;
+use dune_base::{DuneAuthor, DuneBase, DuneProject};
 use dune_post::DunePost;
-use dune_router::DuneRouter;
+use dune_page::{DunePageTemplate, DuneStaticPage};
+use dune_router::{self, DuneRouter};
+use feed;
+use taxonomy::DuneCategory;
+use utils::escape_html;
+use tangle::{self, DuneTangle};
+use assets;
 
 use std::ops::Deref;
 
@@ -72,17 +76,74 @@ struct OverviewTemplate<'a, Router> where Router: 'a + DuneRouter {
 struct PostTemplate<'a, Router> where Router: 'a + DuneRouter {
     pagination: &'a Option<DunePagination>,
     post: &'a DunePost,
+    related: Vec<&'a DunePost>,
+    _parent: BaseTemplate<'a, Router>
+}
+
+/// This template is used for rendering a static Page
+/// I.e. About, next to the sidebar
+#[derive(Template)]
+#[template(path = "page.html", escape = "none")]
+struct PageTemplate<'a, Router> where Router: 'a + DuneRouter {
+    page: &'a DuneStaticPage,
+    _parent: BaseTemplate<'a, Router>
+}
+
+/// This template is used for rendering a wide static Page
+/// I.e. Projects, without the sidebar
+#[derive(Template)]
+#[template(path = "page_wide.html", escape = "none")]
+struct WidePageTemplate<'a, Router> where Router: 'a + DuneRouter {
+    page: &'a DuneStaticPage,
+    _parent: BaseTemplate<'a, Router>
+}
+
+/// This template is used for rendering the Projects
+/// I.e. a card for each project
+#[derive(Template)]
+#[template(path = "projects.html", escape = "none")]
+struct ProjectsTemplate<'a, Router> where Router: 'a + DuneRouter {
+    projects: &'a Vec<DuneProject>,
     _parent: BaseTemplate<'a, Router>
 }
 
+/// This template is used for rendering the profile of an Author
+/// with their posts
+#[derive(Template)]
+#[template(path = "author.html", escape = "none")]
+struct AuthorTemplate<'a, Router> where Router: 'a + DuneRouter {
+    author: &'a DuneAuthor,
+    _parent: BaseTemplate<'a, Router>
+}
+
+/// This template is used for rendering a Project
+/// I.e. the description and the posts about it
+#[derive(Template)]
+#[template(path = "project.html", escape = "none")]
+struct ProjectTemplate<'a, Router> where Router: 'a + DuneRouter {
+    project: &'a DuneProject,
+    _parent: BaseTemplate<'a, Router>
+}
+
+/// This template is used for rendering a Redirect
+/// I.e. an old url of a post
+#[derive(Template)]
+#[template(path = "redirect.html", escape = "none")]
+struct RedirectTemplate<'a> {
+    target: &'a str,
+    config: &'a Rc<Configuration>
+}
+
 // MOVE ROUTER INTO A NEWTYPE WRAPPRE?
 struct RouterWraper<T: DuneRouter> {
-    __router: PhantomData<T>
+    __router: PhantomData<T>,
+    /// Posts in other languages get a prefix
+    language: String
 }
 
 impl<T> RouterWraper<T> where T: DuneRouter {
     fn post(&self, post: &DunePost) -> String {
-        T::post(post)
+        dune_router::post_url::<T>(post, &self.language)
     }
     fn tag(&self, tag: &str) -> String {
         T::tag(tag)
@@ -93,6 +154,31 @@ impl<T> RouterWraper<T> where T: DuneRouter {
     fn keyword(&self, keyword: &str) -> String {
         T::keyword(keyword)
     }
+    fn static_page(&self, page: &DuneStaticPage) -> String {
+        T::static_page(page)
+    }
+    fn series(&self, series: &str) -> String {
+        T::series(series)
+    }
+    fn category(&self, category: &DuneCategory) -> String {
+        T::category(category)
+    }
+    fn projects(&self) -> String {
+        T::projects()
+    }
+    fn project(&self, project: &DuneProject) -> String {
+        T::project(project)
+    }
+    fn author(&self, author: &DuneAuthor) -> String {
+        T::author(author)
+    }
+    /// The avatar next to the profile page of the author
+    fn avatar(&self, author: &DuneAuthor) -> String {
+        match author.avatar {
+            Some(ref avatar) => assets::rewrite_link(avatar, &author.assets, assets::folder_url(&T::author(author))),
+            None => String::new()
+        }
+    }
 }
 
 impl HTMLWriter {
@@ -107,15 +193,30 @@ impl HTMLWriter {
             base: base,
             config: &self.configuration,
             router: RouterWraper {
-                __router: PhantomData::<Router>
+                __router: PhantomData::<Router>,
+                language: self.configuration.language().to_owned()
             }
         }
     }
 
-    fn create_file(&self, path: &Path, contents: &str) -> Result<(), Box<Error>> {
+    /// The project with links to its assets in the project folder
+    fn project_urls<Router: DuneRouter>(project: &DuneProject) -> DuneProject {
+        let mut project = project.clone();
+        let url = Router::project(&project);
+        let url = assets::folder_url(&url);
+        project.contents = assets::rewrite(&project.contents, &project.assets, url);
+        let screenshot = project.screenshot.as_ref()
+            .map(|screenshot| assets::rewrite_link(screenshot, &project.assets, url));
+        project.screenshot = screenshot;
+        project
+    }
+
+    fn create_file(&self, path: &Path, contents: &str) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
         let mut file = fs::File::create(&path)?;
-        file.write_all(contents.as_bytes())?;
-        Ok(())
+        file.write_all(contents.as_bytes())
     }
 }
 
@@ -123,15 +224,20 @@ impl DuneWriter for HTMLWriter {
     fn write<Router: DuneRouter>(&self, database: &Rc<DuneBase>, action: &DuneAction, router: &Router) -> io::Result<()> {
         match action {
             &DuneAction::Post(ref path, ref pagination, ref title, ref post) => {
+                let mut post = post.clone();
+                if !post.assets.is_empty() {
+                    assets::copy(path.parent().unwrap(), &post.assets)?;
+                    post.contents = assets::rewrite(&post.contents, &post.assets,
+                                                    assets::folder_url(&dune_router::post_url::<Router>(&post, self.configuration.language())));
+                }
                 let structure = PostTemplate {
                     pagination: pagination,
-                    post: post,
+                    post: &post,
+                    related: database.related_to(&post),
                     _parent: self.base_template(database, router)
                 };
-                // FIXME: Remove unwrap
-                let rendered = structure.render().unwrap();
-                fs::create_dir_all(path.parent().unwrap());
-                self.create_file(path, &rendered);
+                let rendered = structure.render().map_err(template_error)?;
+                self.create_file(path, &rendered)?;
             },
             &DuneAction::List(ref path, ref pagination, ref title, ref posts, overview) => {
                 println!("path: {:?}, pag: {}", &path, &pagination.is_some());
@@ -141,18 +247,98 @@ impl DuneWriter for HTMLWriter {
                         pagination: pagination,
                         posts: posts,
                         _parent: base
-                    }.render().unwrap(),
+                    }.render().map_err(template_error)?,
                     true => OverviewTemplate {
                         pagination: pagination,
                         posts: posts,
                         _parent: base
-                    }.render().unwrap()
+                    }.render().map_err(template_error)?
+                };
+                self.create_file(path, &rendered)?;
+            },
+            &DuneAction::Page(ref path, ref page) => {
+                let mut page = page.clone();
+                if !page.assets.is_empty() {
+                    assets::copy(path.parent().unwrap(), &page.assets)?;
+                    page.contents = assets::rewrite(&page.contents, &page.assets,
+                                                    assets::folder_url(&Router::static_page(&page)));
+                }
+                let base = self.base_template(database, router);
+                let rendered = match page.template {
+                    DunePageTemplate::Page => PageTemplate {
+                        page: &page,
+                        _parent: base
+                    }.render().map_err(template_error)?,
+                    DunePageTemplate::Wide => WidePageTemplate {
+                        page: &page,
+                        _parent: base
+                    }.render().map_err(template_error)?
                 };
-                fs::create_dir_all(path.parent().unwrap());
-                self.create_file(path, &rendered);
+                self.create_file(path, &rendered)?;
+            },
+            &DuneAction::Projects(ref path, ref projects) => {
+                let projects: Vec<DuneProject> = projects.iter()
+                    .map(|project| HTMLWriter::project_urls::<Router>(project))
+                    .collect();
+                let rendered = ProjectsTemplate {
+                    projects: &projects,
+                    _parent: self.base_template(database, router)
+                }.render().map_err(template_error)?;
+                self.create_file(path, &rendered)?;
+            },
+            &DuneAction::Project(ref path, ref project) => {
+                assets::copy(path.parent().unwrap(), &project.assets)?;
+                let project = HTMLWriter::project_urls::<Router>(project);
+                let rendered = ProjectTemplate {
+                    project: &project,
+                    _parent: self.base_template(database, router)
+                }.render().map_err(template_error)?;
+                self.create_file(path, &rendered)?;
+            },
+            &DuneAction::Author(ref path, ref author) => {
+                assets::copy(path.parent().unwrap(), &author.assets)?;
+                let mut author = author.clone();
+                let url = Router::author(&author);
+                let url = assets::folder_url(&url);
+                author.bio = assets::rewrite(&author.bio, &author.assets, url);
+                let avatar = author.avatar.as_ref()
+                    .map(|avatar| assets::rewrite_link(avatar, &author.assets, url));
+                author.avatar = avatar;
+                let rendered = AuthorTemplate {
+                    author: &author,
+                    _parent: self.base_template(database, router)
+                }.render().map_err(template_error)?;
+                self.create_file(path, &rendered)?;
+            },
+            &DuneAction::Tangle(ref path, mode, ref files) => {
+                match mode {
+                    DuneTangle::Files => tangle::write_files(path, files)?,
+                    DuneTangle::Zip => tangle::write_zip(path, files)?,
+                }
+            },
+            &DuneAction::Feed(ref path, ref title, ref language, ref posts) => {
+                let html_folder = Path::new(self.configuration.html_folder());
+                let feed_url = format!("/{}", path.strip_prefix(html_folder).unwrap_or(path).display());
+                let default_language = self.configuration.language();
+                let rendered = feed::atom(title, self.configuration.base_url(), &feed_url, language, posts,
+                                          &database.authors, |post| dune_router::post_url::<Router>(post, default_language));
+                self.create_file(path, &rendered)?;
+            },
+            &DuneAction::Redirect(ref path, ref target) => {
+                let target = escape_html(target);
+                let rendered = RedirectTemplate {
+                    target: &target,
+                    config: &self.configuration
+                }.render().map_err(template_error)?;
+                self.create_file(path, &rendered)?;
             },
         };
         Ok(())
     }
 
 }
+
+/// Template errors are io errors, so that `Dune::execute` reports them
+fn template_error<E: fmt::Display>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("Could not render the template: {}", error))
+}
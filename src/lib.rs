@@ -1,22 +1,92 @@
 extern crate sha2;
 #[macro_use] extern crate askama;
 extern crate ammonia;
+extern crate yaml_rust;
+extern crate pulldown_cmark;
+extern crate chrono;
+extern crate zip;
 
 mod org_parser;
+mod markdown_parser;
+mod renderer;
 
 mod harness;
 mod configuration;
 mod dune_writer;
 mod html_writer;
 mod dune_base;
+mod dune_index;
+mod related;
+pub mod taxonomy;
 mod traits;
 mod dune_router;
+mod dune_diagnostic;
+mod feature_image;
+mod tangle;
+mod page_parser;
+mod project_parser;
+mod author_parser;
+mod assets;
+mod feed;
+mod toc;
 mod utils;
 pub mod dune_post;
+pub mod dune_page;
 
-pub fn make() {
-    //let parser = org_parser::OrgParser::new("/home/terhechte/Development/Rust/rusttest1/posts", 1);
-    let parser = org_parser::OrgParser::new("/Users/terhechte/Development/Rust/rusttest1/posts", 1);
-    let posts = parser.parse();
-    //println!("{:?}", posts);
+use std::error::Error;
+use std::path::Path;
+use std::rc::Rc;
+
+pub use org_parser::OrgParser;
+pub use markdown_parser::MarkdownParser;
+pub use page_parser::PageParser;
+pub use project_parser::ProjectParser;
+pub use author_parser::AuthorParser;
+pub use renderer::{DuneRenderer, DuneRenderers};
+
+// The build DSL: `Dune::load(configuration)?.builder().group_by(..).with(..)`,
+// then `execute` with a writer and a router
+pub use harness::{Dune, DuneBaseAggType, ActionReceiver, Builder, PostBuilder,
+                  GroupedDuneBuilder, PagedDuneBuilder, PageDuneBuilder};
+pub use harness::{DuneBuilder, DuneBuildMapper, DuneBuildFlatter, DuneBuildCollector, DuneBuildWriter};
+pub use traits::{DunePathBuilder, DunePostSource};
+pub use dune_writer::{DuneAction, DunePagination, DuneWriter};
+pub use html_writer::HTMLWriter;
+pub use dune_router::DuneRouter;
+pub use dune_base::{DuneAuthor, DuneBase, DuneGroup, DuneProject, DuneProjectStatus, DuneSeries};
+pub use dune_index::DuneIndex;
+pub use assets::DuneAsset;
+pub use toc::DuneHeading;
+pub use tangle::DuneTangle;
+
+pub use configuration::{Configuration, FolderConfiguration};
+pub use dune_diagnostic::{DuneDiagnostic, DuneParseResult, DuneSeverity};
+pub use org_parser::org_error::{OrgError, OrgErrorKind};
+pub use org_parser::cache_db::{CacheDB, CacheError, CacheStats};
+
+/// Parse the blog like `Dune::load`, printing the diagnostics. Nothing
+/// is rendered, previewing drafts needs a build that `execute`s a writer.
+pub fn make(configuration: Rc<Configuration>) -> Result<(), Box<Error>> {
+    Dune::load(configuration)?;
+    Ok(())
+}
+
+/// Print all source blocks of the org posts by language and
+/// optionally write them into `export` to compile check them
+pub fn snippets(configuration: &Configuration, export: Option<&Path>) -> Result<(), Box<Error>> {
+    // Posts in subfolders and bundles, like the parsers find them
+    let paths = utils::post_files(configuration.post_folder())?;
+    let mut snippets: Vec<tangle::DuneSnippet> = Vec::new();
+    for path in paths {
+        let identifier = match utils::post_name(&path) {
+            Some(n) => n,
+            None => continue,
+        };
+        snippets.extend(tangle::snippets(&identifier, &path)?);
+    }
+    print!("{}", tangle::report(&snippets));
+    if let Some(folder) = export {
+        println!("Exported {} snippets to {}", tangle::export(folder, &snippets)?, folder.display());
+    }
+    Ok(())
 }
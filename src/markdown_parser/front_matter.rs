@@ -0,0 +1,99 @@
+use yaml_rust::{Yaml, YamlLoader};
+
+use org_parser::fileinfo::{normalize_key, FileMeta};
+use org_parser::org_error::{OrgError, OrgErrorKind};
+
+/// The line that opens and closes a front matter block
+const DELIMITER: &'static str = "---";
+
+/// Split the contents of a file into the front matter (without the
+/// delimiters) and the remaining body. Files that don't start with
+/// a `---` line have no front matter.
+pub fn split(contents: &str) -> (Option<&str>, &str) {
+    let start = match contents.find('\n') {
+        Some(n) if contents[..n].trim_right() == DELIMITER => n + 1,
+        _ => return (None, contents),
+    };
+    let mut offset = start;
+    for line in contents[start..].split('\n') {
+        let next = ::std::cmp::min(offset + line.len() + 1, contents.len());
+        if line.trim_right() == DELIMITER {
+            return (Some(&contents[start..offset]), &contents[next..]);
+        }
+        offset = next;
+    }
+    (None, contents)
+}
+
+/// Parse the YAML front matter into the same key / value pairs that
+/// `FileInfo` collects from org files. The line numbers are those of
+/// the post file, which starts with the opening `---`.
+pub fn parse(front_matter: &str) -> Result<Vec<FileMeta>, OrgError> {
+    let documents = YamlLoader::load_from_str(front_matter)
+        .map_err(|e| OrgError::new(OrgErrorKind::MalformedMetadata, format!("Invalid front matter: {}", e))
+                 .line(Some(e.marker().line() + 1)))?;
+    let mut result: Vec<FileMeta> = Vec::new();
+    let hash = match documents.into_iter().nth(0) {
+        Some(Yaml::Hash(hash)) => hash,
+        None | Some(Yaml::Null) => return Ok(result),
+        Some(_) => return Err(OrgError::new(OrgErrorKind::MalformedMetadata,
+                                            "Front matter is not a map of keys and values")),
+    };
+    for (key, value) in hash {
+        let key = match key.as_str() {
+            Some(n) => n.to_owned(),
+            None => continue,
+        };
+        if let Some(value) = value_string(&value) {
+            result.push(FileMeta {
+                key_name: normalize_key(&key),
+                value: value,
+                line: key_line(front_matter, &key),
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// The yaml parser doesn't keep the position of keys, but
+/// top level keys are at the start of a line
+fn key_line(front_matter: &str, key: &str) -> Option<usize> {
+    let prefix = format!("{}:", key);
+    front_matter.lines()
+        .position(|line| line.starts_with(&prefix))
+        .map(|n| n + 2)
+}
+
+fn value_string(value: &Yaml) -> Option<String> {
+    match *value {
+        Yaml::String(ref n) | Yaml::Real(ref n) => Some(n.clone()),
+        Yaml::Integer(n) => Some(n.to_string()),
+        Yaml::Boolean(n) => Some(n.to_string()),
+        Yaml::Array(ref items) => Some(items.iter()
+            .filter_map(value_string)
+            .collect::<Vec<String>>()
+            .join(" ")),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_front_matter() {
+    let contents = "---
+title: Blogstrapped.
+tags: entrepeneurship blog
+alias: [\"/2011/12/index.html\"]
+---
+Finally. I started the adventure.
+";
+    let (matter, body) = split(contents);
+    assert_eq!(body, "Finally. I started the adventure.\n");
+    let meta = parse(matter.unwrap()).unwrap();
+    assert_eq!(meta[0].key_name, "title");
+    assert_eq!(meta[0].value, "Blogstrapped.");
+    assert_eq!(meta[1].value, "entrepeneurship blog");
+    assert_eq!(meta[2].value, "/2011/12/index.html");
+    assert_eq!(meta[1].line, Some(3));
+    assert_eq!(parse("title: [a").unwrap_err().kind, OrgErrorKind::MalformedMetadata);
+    assert_eq!(split("no front matter"), (None, "no front matter"));
+}
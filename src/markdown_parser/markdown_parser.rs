@@ -0,0 +1,127 @@
+use dune_diagnostic::DuneParseResult;
+use dune_post::DunePost;
+use markdown_parser::front_matter;
+use org_parser::cache_db::CacheDB;
+use org_parser::fileinfo::{FileInfo, FileMeta};
+use org_parser::org_error::{OrgError, OrgErrorKind};
+use renderer::DuneRenderers;
+use traits::DunePostSource;
+use utils::{post_files, post_name};
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::FixedOffset;
+
+/// Parses markdown (and plain html) posts that carry their metadata
+/// as YAML front matter, i.e. the old Octopress / Jekyll posts.
+pub struct MarkdownParser {
+    folder: PathBuf,
+    markdown_extensions: Vec<&'static str>,
+    renderers: DuneRenderers,
+    cache_db: Arc<CacheDB>,
+    /// For dates without an offset
+    timezone: FixedOffset,
+}
+
+impl MarkdownParser {
+    pub fn new<T: AsRef<Path>>(folder: T, cache_db: Arc<CacheDB>) -> MarkdownParser {
+        MarkdownParser {
+            folder: folder.as_ref().to_owned(),
+            markdown_extensions: vec!["markdown", "md", "html"],
+            renderers: DuneRenderers::default(),
+            cache_db,
+            timezone: FixedOffset::east(0),
+        }
+    }
+
+    pub fn renderers(mut self, renderers: DuneRenderers) -> Self {
+        self.renderers = renderers;
+        self
+    }
+
+    pub fn timezone(mut self, timezone: FixedOffset) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    fn is_markdown(&self, path: &Path) -> bool {
+        match path.extension().and_then(|n| n.to_str()) {
+            Some(extension) => self.markdown_extensions.contains(&extension),
+            None => false,
+        }
+    }
+
+    fn parse_post(&self, path: &Path) -> DuneParseResult {
+        let mut result = DuneParseResult::new();
+        match self.parse_fileinfo(path) {
+            Ok((filename, name, contents, fileinfo)) => {
+                for warning in fileinfo.warnings() {
+                    result.warning(path, warning);
+                }
+                let post = DunePost::render_cached(
+                    &filename, path, &contents, &fileinfo, &self.cache_db, &self.renderers)
+                    .and_then(|contents_html| DunePost::with_info(&name, path, &fileinfo, contents_html, &self.timezone));
+                match post {
+                    Ok(post) => result.posts.push(post),
+                    Err(e) => result.error(path, e),
+                }
+            }
+            Err(e) => result.error(path, e),
+        }
+        result
+    }
+
+    /// The filename, the name without extension, the contents and the metadata of a post
+    fn parse_fileinfo(&self, path: &Path) -> Result<(String, String, String, FileInfo), OrgError> {
+        // The path inside the post folder identifies the post in the cache
+        let relative = path.strip_prefix(&self.folder).unwrap_or(path);
+        let (filename, name) = match (relative.to_str(), post_name(path)) {
+            (Some(filename), Some(name)) => (filename.to_owned(), name),
+            _ => return Err(OrgError::new(OrgErrorKind::BadFilename, format!("Invalid filename {:?}", path))),
+        };
+        let mut contents = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(|e| OrgError::new(OrgErrorKind::UnreadableFile,
+                                       format!("Could not read file {}: {}", &name, e)))?;
+
+        let mut meta = match front_matter::split(&contents) {
+            (Some(matter), _) => front_matter::parse(matter)?,
+            (None, _) => Vec::new(),
+        };
+        // The old posts never had a table of contents, so it has to be enabled explicitly
+        if !meta.iter().any(|entry| entry.key_name == "toc") {
+            meta.push(FileMeta {
+                key_name: "toc".to_owned(),
+                value: "false".to_owned(),
+                line: None,
+            });
+        }
+        let fileinfo = FileInfo {
+            meta: meta,
+            sources: Vec::new(),
+        };
+        Ok((filename, name, contents, fileinfo))
+    }
+}
+
+impl DunePostSource for MarkdownParser {
+    fn parse(&self) -> DuneParseResult {
+        let mut result = DuneParseResult::new();
+        let paths: Vec<PathBuf> = match post_files(&self.folder) {
+            Ok(paths) => paths.into_iter().filter(|path| self.is_markdown(path)).collect(),
+            Err(e) => {
+                result.error(&self.folder, OrgError::new(OrgErrorKind::UnreadableFile,
+                                                         format!("Could not read the post folder: {}", e)));
+                return result;
+            }
+        };
+        for path in paths {
+            result.extend(self.parse_post(&path));
+        }
+        result
+    }
+}
@@ -0,0 +1,4 @@
+pub mod front_matter;
+mod markdown_parser;
+
+pub use self::markdown_parser::MarkdownParser;
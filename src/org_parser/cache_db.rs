@@ -1,24 +1,178 @@
 use org_parser::rustbreak::Database;
-use std::path::Path;
 
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The cache entry that lists the identifiers of all other entries
+const ENTRIES_KEY: &'static str = "__entries";
+
+#[derive(Debug)]
+pub enum CacheError {
+    /// The cache file could not be opened or created
+    Open(String),
+    /// The cache file exists but could not be decoded
+    Corrupt(String),
+    /// Writing the cache back to disk failed
+    Write(String),
+}
+
+impl Error for CacheError {
+    fn description(&self) -> &str {
+        match self {
+            &CacheError::Open(ref message) => message,
+            &CacheError::Corrupt(ref message) => message,
+            &CacheError::Write(ref message) => message,
+        }
+    }
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &CacheError::Open(ref message) => write!(f, "Could not open cache: {}", message),
+            &CacheError::Corrupt(ref message) => write!(f, "Corrupt cache, run `cache clear`: {}", message),
+            &CacheError::Write(ref message) => write!(f, "Could not write cache: {}", message),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CacheStats {
+    /// Number of cached posts
+    pub entries: usize,
+    /// Size of all the cached html in bytes
+    pub contents_size: usize,
+    /// Size of the cache file on disk in bytes
+    pub file_size: u64,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} entries, {} bytes of html, {} bytes on disk",
+               self.entries, self.contents_size, self.file_size)
+    }
+}
+
+/// Stores the rendered html of the posts. Changes are only written
+/// to disk on `flush`, which should happen once per build.
+/// The cache is shared between the parser threads.
 pub struct CacheDB {
-    database: Database<String>
+    database: Database<String>,
+    /// The identifiers of all entries, so that they can be listed
+    entries: Mutex<BTreeSet<String>>,
+    path: PathBuf,
 }
 
 impl CacheDB {
-    pub fn new<T: AsRef<Path>>(cache_file: T) -> CacheDB {
-        let database = Database::<String>::open(cache_file.as_ref()).unwrap();
-        CacheDB {
-            database
+    pub fn open<T: AsRef<Path>>(cache_file: T) -> Result<CacheDB, CacheError> {
+        let path = cache_file.as_ref().to_owned();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| CacheError::Open(format!("{}: {}", path.display(), e)))?;
+            }
+        }
+        let database = Database::<String>::open(&path).map_err(|e| match path.metadata() {
+            // There is something in the file, but we couldn't read it
+            Ok(ref metadata) if metadata.len() > 0 => CacheError::Corrupt(format!("{}: {}", path.display(), e)),
+            _ => CacheError::Open(format!("{}: {}", path.display(), e)),
+        })?;
+        let entries: Vec<String> = database.retrieve(ENTRIES_KEY).unwrap_or(Vec::new());
+        Ok(CacheDB {
+            database,
+            entries: Mutex::new(entries.into_iter().collect()),
+            path,
+        })
+    }
+
+    /// The cached html for the post, unless it was rendered from different
+    /// contents or with a different renderer. `key` is the content hash plus
+    /// the renderer fingerprint.
+    pub fn cache_entry(&self, identifier: &str, key: &str) -> Option<String> {
+        // Entries from older versions (without a key) fail to decode and count as stale
+        let (cached_key, contents): (String, String) = self.database.retrieve(identifier).ok()?;
+        if cached_key != key {
+            return None;
         }
+        Some(contents)
     }
 
-    pub fn cache_entry(&self, identifier: &str) -> Option<String> {
-        self.database.retrieve(identifier).ok()
+    pub fn set_cache_entry(&self, identifier: &str, key: &str, contents: &str) -> Result<(), CacheError> {
+        self.database.insert(identifier, (key.to_owned(), contents.to_owned()))
+            .map_err(|e| CacheError::Write(format!("{}: {}", identifier, e)))?;
+        self.entries.lock().unwrap().insert(identifier.to_owned());
+        Ok(())
     }
 
-    pub fn set_cache_entry(&self, identifier: &str, contents: &str) {
-        self.database.insert(identifier, contents.clone());
-        self.database.flush().unwrap();
+    /// Write all changes to disk
+    pub fn flush(&self) -> Result<(), CacheError> {
+        let entries: Vec<String> = self.entries.lock().unwrap().iter().cloned().collect();
+        self.database.insert(ENTRIES_KEY, entries)
+            .and_then(|_| self.database.flush())
+            .map_err(|e| CacheError::Write(format!("{}: {}", self.path.display(), e)))
     }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.entries.lock().unwrap();
+        let contents_size = entries.iter()
+            .filter_map(|identifier| self.database.retrieve(identifier.as_str()).ok())
+            .map(|(_, contents): (String, String)| contents.len())
+            .sum();
+        CacheStats {
+            entries: entries.len(),
+            contents_size: contents_size,
+            file_size: self.path.metadata().map(|n| n.len()).unwrap_or(0),
+        }
+    }
+
+    /// Remove the entries of posts that don't exist in the post folder anymore.
+    /// Returns the identifiers of the removed entries. rustbreak can't list its
+    /// keys, so the cache is rebuilt from the entries that are kept. This also
+    /// drops entries that are not in the index, i.e. from older versions.
+    pub fn prune<T: AsRef<Path>>(self, post_folder: T) -> Result<Vec<String>, CacheError> {
+        let (kept, removed): (Vec<String>, Vec<String>) = self.entries.lock().unwrap().iter()
+            .cloned()
+            .partition(|identifier| post_folder.as_ref().join(identifier).exists());
+        let kept: Vec<(String, (String, String))> = kept.into_iter()
+            .filter_map(|identifier| self.database.retrieve(identifier.as_str()).ok()
+                        .map(|entry| (identifier, entry)))
+            .collect();
+        let path = self.path.clone();
+        drop(self);
+        CacheDB::clear_file(&path)?;
+        let cache = CacheDB::open(&path)?;
+        for (identifier, (key, contents)) in kept {
+            cache.set_cache_entry(&identifier, &key, &contents)?;
+        }
+        cache.flush()?;
+        Ok(removed)
+    }
+
+    /// Remove the cache file without decoding it, so that a corrupt
+    /// cache can be cleared as well
+    pub fn clear_file<T: AsRef<Path>>(cache_file: T) -> Result<(), CacheError> {
+        let path = cache_file.as_ref();
+        match fs::remove_file(path) {
+            Err(ref e) if e.kind() != io::ErrorKind::NotFound =>
+                Err(CacheError::Write(format!("{}: {}", path.display(), e))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_clear_file() {
+    use std::io::prelude::*;
+    let path = ::std::env::temp_dir().join("wanderdune-test-cache").join("corrupt.db");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::File::create(&path).unwrap().write_all(b"\x00 not a cache").unwrap();
+    CacheDB::clear_file(&path).unwrap();
+    assert!(!path.exists());
+    // Clearing a cache that doesn't exist is fine
+    CacheDB::clear_file(&path).unwrap();
 }
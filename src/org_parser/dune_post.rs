@@ -1,19 +1,21 @@
-use self::super::super::dune_post::{DunePost, DunePostTime};
+use self::super::super::dune_post::{DunePost, DunePostTime, DuneMetaValue, DunePublication};
 use org_parser::cache_db::CacheDB;
-use org_parser::org_error::OrgError;
+use org_parser::org_error::{OrgError, OrgErrorKind};
 use org_parser::fileinfo::FileInfo;
+use renderer::DuneRenderers;
+use utils::post_name;
+use assets::find_assets;
+use toc::anchor_headings;
 
+use std::collections::BTreeMap;
 use std::fs; use std::path::{Path, PathBuf};
 use std::io::prelude::*;
 use std::error::Error;
 use std::fmt;
 use std::fs::File;
-use std::process::Command;
 use std::str;
 use sha2::{Sha256, Digest};
-
-// Include the pandoc template
-const PANDOC_HTML: &'static str = include_str!("pandoc.html");
+use chrono::{FixedOffset, Utc};
 
 fn slurp<T: AsRef<Path>>(path: T) -> String {
     let mut buf = String::new();
@@ -22,109 +24,162 @@ fn slurp<T: AsRef<Path>>(path: T) -> String {
     buf
 }
 
-fn spit<T: AsRef<Path>>(path: T, contents: &str)  {
-    let mut file = File::create(path).unwrap();
-    file.write(contents.as_bytes());
-}
-
 impl DunePost {
 
     pub fn new(
         filename: &str,
         path: &PathBuf,
-        cache: &CacheDB)
+        fileinfo: &FileInfo,
+        cache: &CacheDB,
+        renderers: &DuneRenderers,
+        timezone: &FixedOffset)
         -> Result<DunePost, OrgError> {
-        let name = filename.clone().replace(".org", "");
-        let (title, route, year, month, day) = match DunePost::parse_filename(&name) {
-            Some(n) => n,
-            None => {
-                return Err(OrgError {
-                    message: format!("Could not parse filename {}", &name),
-                })
-            }
-        };
-        // we will read the file twice, which is ok for this simple, awful, blog project
-        //let contents =
-        let contents: String = match File::open(&path).ok().and_then(move |mut file| {
-            let mut buf = String::new();
-            file.read_to_string(&mut buf);
-            return Some(buf);
-        }) {
-            Some(b) => b,
-            None => {
-                return Err(
-                    OrgError { message: format!("Could read file {}", &name) },
-                )
-            }
-        };
+        // `<name>.org` or a bundle, `<name>/index.org`
+        let name = post_name(path)
+            .ok_or(OrgError::new(OrgErrorKind::BadFilename, format!("Invalid filename {}", filename)))?;
+        // bail out before we render anything
+        DunePost::parse_filename(&name)?;
+        let mut contents = String::new();
+        File::open(&path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(|e| OrgError::new(OrgErrorKind::UnreadableFile,
+                                       format!("Could not read file {}: {}", &name, e)))?;
+
+        let contents_html = DunePost::render_cached(filename, &path, &contents, fileinfo, cache, renderers)?;
+
+        DunePost::with_info(&name, path, fileinfo, contents_html, timezone)
+    }
+
+    /// Render the post with the matching renderer, unless the cache has
+    /// html for the same contents and the same renderer
+    pub fn render_cached(
+        identifier: &str,
+        path: &Path,
+        contents: &str,
+        fileinfo: &FileInfo,
+        cache: &CacheDB,
+        renderers: &DuneRenderers)
+        -> Result<String, OrgError> {
+        let renderer = renderers.renderer_for(path, fileinfo)
+            .ok_or(OrgError::new(OrgErrorKind::RendererFailure, format!("No renderer for {}", identifier)))?;
 
         let mut hasher = Sha256::default();
-        hasher.input(&contents.as_bytes());
-        let hash = hasher.result();
-        let identifier = &filename.clone();
-
-        let fileinfo = FileInfo::new(path.as_path());
-
-        let contents_html: String;
-        if let Some(retrieved_content) = cache.cache_entry(identifier) {
-            contents_html = retrieved_content;
-        } else {
-            contents_html = DunePost::render_pandoc(&path, fileinfo.has_toc())
-                .map_err(|e|OrgError { message: format!("Error: {}", e) })?;
-            cache.set_cache_entry(identifier, &contents_html);
+        hasher.input(contents.as_bytes());
+        let cache_key = format!("{:x} {}", hasher.result(), renderer.fingerprint(fileinfo));
+
+        if let Some(retrieved_content) = cache.cache_entry(identifier, &cache_key) {
+            return Ok(retrieved_content);
+        }
+        let contents_html = renderer.render(path, fileinfo)
+            .map_err(|e| OrgError::new(OrgErrorKind::RendererFailure,
+                                       format!("{} failed: {}", renderer.name(), e)))?;
+        // The post is rendered, it just has to be rendered again next time
+        if let Err(e) = cache.set_cache_entry(identifier, &cache_key, &contents_html) {
+            println!("{}", e);
         }
+        Ok(contents_html)
+    }
+
+    /// Create a post from the name of the file (without the extension),
+    /// its path, the metadata and the already rendered html. This is shared by all
+    /// post sources, regardless of where the metadata came from.
+    /// `#+date:` overrides the date from the filename.
+    pub fn with_info(name: &str, source: &Path, fileinfo: &FileInfo, contents_html: String,
+                     timezone: &FixedOffset) -> Result<DunePost, OrgError> {
+        let name = name.to_owned();
+        let (title, route, year, month, day) = DunePost::parse_filename(&name)?;
 
+        let title = fileinfo.title().unwrap_or(title);
         let tags = fileinfo.tags();
         let keywords = fileinfo.keywords();
         let description = fileinfo.desc();
-        let enabled = fileinfo.is_enabled();
+        let aliases = fileinfo.aliases();
+        let authors = fileinfo.authors();
+        let (series, series_part) = fileinfo.series();
+        let (language, translation_of) = fileinfo.translation();
+
+        let released = match fileinfo.meta_entry("date") {
+            Some(entry) => DunePostTime::parse(&entry.value, timezone).ok_or(OrgError::new(
+                OrgErrorKind::InvalidDate, format!("Invalid date {}", &entry.value)).line(entry.line))?,
+            None => {
+                let released = match (year.parse::<i32>(), month.parse::<i32>(), day.parse::<i32>()) {
+                    (Ok(y), Ok(m), Ok(d)) => DunePostTime::new(y, m, d, None, timezone),
+                    _ => None
+                };
+                released.ok_or(OrgError::new(
+                    OrgErrorKind::InvalidDate, format!("Invalid date {}-{}-{} in {}", year, month, day, &name)))?
+            }
+        };
 
-        let year_number = year.to_string().parse::<i32>().unwrap();
-        let month_number = month.to_string().parse::<i32>().unwrap();
-        let day_number = day.to_string().parse::<i32>().unwrap();
+        let publication = match fileinfo.status().as_ref().map(|n| n.as_str()) {
+            Some("draft") => DunePublication::Draft,
+            Some("unlisted") => DunePublication::Unlisted,
+            _ if !fileinfo.is_enabled() => DunePublication::Draft,
+            _ => DunePublication::Published,
+        };
+        // Posts from the future are published by the first build after their date
+        let publication = match publication {
+            DunePublication::Published if released.timestamp() > Utc::now().timestamp() => DunePublication::Scheduled,
+            other => other,
+        };
+        let enabled = publication == DunePublication::Published || publication == DunePublication::Unlisted;
+
+        // The first entry wins if a key is used more than once
+        let mut meta: BTreeMap<String, DuneMetaValue> = BTreeMap::new();
+        for entry in &fileinfo.meta {
+            meta.entry(entry.key_name.clone())
+                .or_insert_with(|| DuneMetaValue::new(&entry.key_name, &entry.value, timezone));
+        }
+        let (contents_html, toc) = anchor_headings(&contents_html);
+        // Bundles have their images next to the post
+        let assets = find_assets(&contents_html, source.parent().unwrap_or(Path::new(".")));
         Ok(DunePost {
             identifier: name.clone(),
             path: name.clone(),
+            source: source.to_owned(),
             title: title,
-            released: DunePostTime {
-                year: year,
-                month: month,
-                day: day,
-                values: (year_number, month_number, day_number),
-            },
+            released: released,
             contents: contents_html,
+            toc: toc,
             tags: tags,
             keywords: keywords,
             description: description,
-            enabled: enabled
+            enabled: enabled,
+            publication: publication,
+            aliases: aliases,
+            authors: authors,
+            series: series,
+            series_part: series_part,
+            language: language,
+            translation_of: translation_of,
+            feature_image: None,
+            assets: assets,
+            meta: meta
         })
     }
 
-    fn render_pandoc(path: &Path, has_toc: bool) -> Result<String, Box<Error>> {
-        spit("/tmp/htmltemplate.html", PANDOC_HTML);
-        let mut args: Vec<&str> = vec!["--template", "/tmp/htmltemplate.html", "-s", "--highlight-style", "zenburn"];
-        if has_toc {
-            args.push("--toc");
-        }
-        let mut output = Command::new("pandoc")
-            .args(&args)
-            .arg(path.to_str().unwrap())
-            .output()?;
-        if &output.stderr.len() > &0 {
-            println!("errrr: {:?}", str::from_utf8(&output.stderr));
-        }
-        Ok(str::from_utf8(&output.stdout)?.to_owned())
+    /// A post for the tests, with the metadata as `(key, value)` pairs
+    #[cfg(test)]
+    pub fn test_post(name: &str, meta: &[(&str, &str)], contents: &str) -> DunePost {
+        use org_parser::fileinfo::FileMeta;
+        let fileinfo = FileInfo {
+            meta: meta.iter().map(|&(key, value)| FileMeta { key_name: key.to_owned(), value: value.to_owned(), line: None })
+                .collect(),
+            sources: Vec::new(),
+        };
+        DunePost::with_info(name, Path::new(name), &fileinfo, contents.to_owned(), &FixedOffset::east(0)).unwrap()
     }
 
-    fn parse_filename(filename: &String) -> Option<(String, String, String, String, String)> {
+    fn parse_filename(filename: &String) -> Result<(String, String, String, String, String), OrgError> {
         let components: Vec<&str> = filename.split("-").collect();
         if components.len() < 4 {
-            return None;
+            return Err(OrgError::new(OrgErrorKind::BadFilename,
+                                     format!("Expected year-month-day-title, found {}", filename)));
         }
         let title_components = &components[3..];
         let title = title_components.connect(" ");
         let route = title_components.connect("-");
-        return Some((
+        return Ok((
             title,
             route,
             components[0].to_owned(),
@@ -133,3 +188,15 @@ impl DunePost {
         ));
     }
 }
+
+#[test]
+fn test_publication() {
+    let post = |name: &str, key: &str, value: &str| DunePost::test_post(name, &[(key, value)], "");
+    assert_eq!(post("2018-01-10-a", "status", "Unlisted").publication, DunePublication::Unlisted);
+    assert_eq!(post("2018-01-10-a", "inactive", "true").publication, DunePublication::Draft);
+    assert_eq!(post("2018-01-10-a", "published", "false").publication, DunePublication::Draft);
+    let draft = DunePost::test_post("2018-01-10-a", &[("inactive", "false"), ("draft", "true")], "");
+    assert_eq!(draft.publication, DunePublication::Draft);
+    assert_eq!(post("2999-01-10-a", "title", "Future").publication, DunePublication::Scheduled);
+    assert_eq!(post("2018-01-10-a", "title", "Past").publication, DunePublication::Published);
+}
@@ -4,41 +4,106 @@ use std::io::prelude::*;
 use std::io::BufReader;
 use std::fs::File;
 
+use org_parser::org_error::{OrgError, OrgErrorKind};
+
 #[derive(Debug)]
 pub struct FileInfo {
+    /// The `#+key: value` lines, in the order of the file
     pub meta: Vec<FileMeta>,
     pub sources: Vec<FileSource>,
 }
 
 #[derive(Debug)]
 pub struct FileMeta {
+    /// The key as returned by `normalize_key`
     pub key_name: String,
     pub value: String,
+    /// The line of the key in the file, if known
+    pub line: Option<usize>,
 }
 
 #[derive(Debug)]
 pub struct FileSource {
     pub contents: String,
     pub properties: Vec<(String, String)>,
+    /// The line of the `#+BEGIN_SRC`
+    pub line: usize,
+}
+
+impl FileSource {
+    pub fn language(&self) -> Option<&str> {
+        self.properties.iter()
+            .filter(|&&(ref key, _)| !key.starts_with(":"))
+            .map(|&(ref key, _)| key.as_str())
+            .nth(0)
+    }
+
+    /// The value of a `:key value` property, i.e. `property(":tangle")`
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.iter()
+            .filter(|&&(ref name, _)| name == key)
+            .map(|&(_, ref value)| value.as_str())
+            .nth(0)
+    }
 }
 
 #[allow(dead_code)]
 impl FileInfo {
-    pub fn new(path: &Path) -> FileInfo {
+    pub fn new(path: &Path) -> Result<FileInfo, OrgError> {
         match FileInfo::meta_contents_of_file(path) {
-            Ok(x) => {
-                return FileInfo {
-                    meta: x.0,
-                    sources: x.1,
-                }
+            Ok(x) => Ok(FileInfo {
+                meta: x.0,
+                sources: x.1,
+            }),
+            Err(e) => Err(OrgError::new(OrgErrorKind::UnreadableFile,
+                                        format!("Could not read {}: {}", path.display(), e))),
+        }
+    }
+
+    /// Metadata that can be parsed, but probably doesn't mean
+    /// what the author intended
+    pub fn warnings(&self) -> Vec<OrgError> {
+        let mut warnings = self.metadata_warnings();
+        if let Some(entry) = self.meta_entry("status") {
+            if !["published", "draft", "unlisted"].contains(&entry.value.trim().to_lowercase().as_str()) {
+                warnings.push(OrgError::new(
+                    OrgErrorKind::MalformedMetadata,
+                    format!("Expected published, draft or unlisted for status, found '{}'", entry.value)).line(entry.line));
+            }
+        }
+        if let Some(entry) = self.meta_entry("series_part") {
+            if entry.value.trim().parse::<i32>().is_err() {
+                warnings.push(OrgError::new(
+                    OrgErrorKind::MalformedMetadata,
+                    format!("Expected a number for series_part, found '{}'", entry.value)).line(entry.line));
             }
-            Err(_) => {
-                return FileInfo {
-                    meta: Vec::new(),
-                    sources: Vec::new(),
+        }
+        warnings
+    }
+
+    /// The `warnings` that don't depend on `#+status:`, which means
+    /// something else for projects
+    pub fn metadata_warnings(&self) -> Vec<OrgError> {
+        let mut warnings: Vec<OrgError> = Vec::new();
+        for key in &["inactive", "draft", "published", "toc"] {
+            for entry in self.meta.iter().filter(|entry| &entry.key_name == key) {
+                let value = entry.value.trim();
+                // `#+toc: sidebar` puts the table of contents next to the post
+                if value != "true" && value != "false" && !(key == &"toc" && value == "sidebar") {
+                    warnings.push(OrgError::new(
+                        OrgErrorKind::MalformedMetadata,
+                        format!("Expected true or false for {}, found '{}'", key, value)).line(entry.line));
                 }
             }
-        };
+        }
+        for key in &["title", "tags", "keywords", "description"] {
+            for entry in self.meta.iter().filter(|entry| &entry.key_name == key).skip(1) {
+                warnings.push(OrgError::new(
+                    OrgErrorKind::MalformedMetadata,
+                    format!("Duplicate key {}, only the first one is used", key)).line(entry.line));
+            }
+        }
+        warnings
     }
 
     pub fn describe(&self) {
@@ -57,51 +122,152 @@ impl FileInfo {
     }
 }
 
+/// `#+TITLE:`, `+title:` and `title` are all the same key: `title`
+pub fn normalize_key(key: &str) -> String {
+    key.trim()
+        .trim_left_matches('#')
+        .trim_left_matches('+')
+        .trim_right_matches(':')
+        .trim()
+        .to_lowercase()
+}
+
 impl FileInfo {
+    pub fn title(&self) -> Option<String> {
+        match self.meta_contents("title") {
+            Some(n) if !n.trim().is_empty() => Some(n.trim().to_owned()),
+            _ => None,
+        }
+    }
+
     pub fn tags(&self) -> Vec<String> {
-        match self.meta_contents("+tags:") {
-            Some(n) => n.trim().split(" ").map(String::from).collect(),
+        match self.meta_contents("tags") {
+            Some(n) => n.split_whitespace().map(String::from).collect(),
             None => Vec::new(),
         }
     }
 
     pub fn keywords(&self) -> Vec<String> {
-        match self.meta_contents("+keywords:") {
-            Some(n) => n.trim().split(" ").map(String::from).collect(),
+        match self.meta_contents("keywords") {
+            Some(n) => n.split_whitespace().map(String::from).collect(),
             None => Vec::new(),
         }
     }
 
+    /// Old urls of the post, i.e. `#+alias: /2011/12/index.html`.
+    /// Front matter uses a list: `alias: ["/2011/12/index.html"]`
+    pub fn aliases(&self) -> Vec<String> {
+        let mut aliases: Vec<String> = Vec::new();
+        for entry in self.meta.iter().filter(|entry| entry.key_name == "alias" || entry.key_name == "aliases") {
+            aliases.extend(entry.value.split_whitespace().map(String::from));
+        }
+        aliases
+    }
+
+    /// `#+author: Benedikt Terhechte, jane`, comma separated names or
+    /// identifiers of author profiles. Several `#+author:` lines are fine too.
+    pub fn authors(&self) -> Vec<String> {
+        let mut authors: Vec<String> = Vec::new();
+        for entry in self.meta.iter().filter(|entry| entry.key_name == "author" || entry.key_name == "authors") {
+            authors.extend(entry.value.split(',')
+                           .map(|author| author.trim())
+                           .filter(|author| !author.is_empty())
+                           .map(String::from));
+        }
+        authors
+    }
+
+    /// `#+series:` and `#+series_part:`, the part is only used with a series
+    pub fn series(&self) -> (Option<String>, Option<i32>) {
+        let series = match self.meta_contents("series") {
+            Some(n) if !n.trim().is_empty() => n.trim().to_owned(),
+            _ => return (None, None),
+        };
+        let part = self.meta_contents("series_part").and_then(|n| n.trim().parse::<i32>().ok());
+        (Some(series), part)
+    }
+
+    /// `#+language:` and `#+translation_of:`, i.e. `zh` and `2015-10-25-swift-pattern-matching`
+    pub fn translation(&self) -> (Option<String>, Option<String>) {
+        let value = |key: &str| match self.meta_contents(key) {
+            Some(n) if !n.trim().is_empty() => Some(n.trim().to_owned()),
+            _ => None,
+        };
+        (value("language").map(|n| n.to_lowercase()), value("translation_of"))
+    }
+
     pub fn desc(&self) -> String {
-        match self.meta_contents("+description:") {
+        match self.meta_contents("description") {
             Some(n) => n.to_owned(),
             None => String::new(),
         }
     }
 
+    /// `#+inactive: true`, `#+draft: true` and `published: false`
+    /// (Jekyll) all disable a post
     pub fn is_enabled(&self) -> bool {
-        let result = match self.meta_contents("+inactive:") {
+        // `#+inactive: false` doesn't override `#+draft: true`
+        let result = ["inactive", "draft"].iter().any(|key| match self.meta_contents(key) {
             Some(n) => n.contains("true"),
             _ => false
+        });
+        let published = match self.meta_contents("published") {
+            Some(n) => !n.contains("false"),
+            _ => true
         };
-        !result
+        !result && published
     }
 
+    /// `#+status:` is `published`, `draft` or `unlisted`
+    pub fn status(&self) -> Option<String> {
+        match self.meta_contents("status") {
+            Some(n) if !n.trim().is_empty() => Some(n.trim().to_lowercase()),
+            _ => None
+        }
+    }
+
+    /// A table of contents, in the post or with `#+toc: sidebar` next to it
     pub fn has_toc(&self) -> bool {
-        match self.meta_contents("+OPTIONS:") {
+        if let Some(n) = self.meta_contents("toc") {
+            return n.trim() == "true" || n.trim() == "sidebar";
+        }
+        match self.meta_contents("options") {
             Some(n) => !n.contains("toc:nil"),
             _ => true
         }
     }
 
-    pub fn meta_contents<'a>(&'a self, field: &'static str) -> Option<&'a str> {
-        match self.meta
+    pub fn toc_in_sidebar(&self) -> bool {
+        self.meta_contents("toc").map(|n| n.trim() == "sidebar").unwrap_or(false)
+    }
+
+    /// The table of contents the renderer puts into the post, the
+    /// sidebar is rendered by the template
+    pub fn has_inline_toc(&self) -> bool {
+        self.has_toc() && !self.toc_in_sidebar()
+    }
+
+    /// The name of the renderer that should be used instead of
+    /// the one for the file extension
+    pub fn renderer(&self) -> Option<&str> {
+        match self.meta_contents("renderer") {
+            Some(n) if !n.trim().is_empty() => Some(n.trim()),
+            _ => None
+        }
+    }
+
+    /// The value of the first entry for `field`, which can be given
+    /// in any case, with or without the `+` and `:` around it
+    pub fn meta_contents<'a>(&'a self, field: &str) -> Option<&'a str> {
+        self.meta_entry(field).map(|entry| entry.value.as_str())
+    }
+
+    pub fn meta_entry<'a>(&'a self, field: &str) -> Option<&'a FileMeta> {
+        let field = normalize_key(field);
+        self.meta
             .iter()
             .filter(|entry| entry.key_name == field)
-            .nth(0) {
-            Some(n) => Some(&n.value),
-            None => None,
-        }
+            .nth(0)
     }
 }
 
@@ -115,11 +281,9 @@ impl FileInfo {
         let mut is_in_source_block = false;
         let mut current_source: Vec<String> = Vec::new();
         let mut current_properties: Option<Vec<(String, String)>> = None;
-        for line in reader.lines() {
-            let line = match line {
-                Ok(l) => l,
-                Err(_) => continue,
-            };
+        let mut current_line: usize = 0;
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
             if is_in_source_block {
                 if FileInfo::is_source_end(&line) {
                     is_in_source_block = false;
@@ -128,6 +292,7 @@ impl FileInfo {
                             sources.push(FileSource {
                                 contents: current_source.join("\n"),
                                 properties: x,
+                                line: current_line,
                             })
                         }
                         None => (),
@@ -142,6 +307,7 @@ impl FileInfo {
                 is_in_source_block = true;
                 current_source = Vec::new();
                 current_properties = Some(x);
+                current_line = number + 1;
                 continue;
             }
             match FileInfo::find_line_meta(line) {
@@ -149,6 +315,7 @@ impl FileInfo {
                     result.push(FileMeta {
                         key_name: x.0,
                         value: x.1,
+                        line: Some(number + 1),
                     })
                 }
                 None => continue,
@@ -157,34 +324,41 @@ impl FileInfo {
         return Ok((result, sources));
     }
 
+    /// `#+BEGIN_SRC swift :tangle example.swift` returns the language and the
+    /// `:key value` properties, i.e. `[("swift", ""), (":tangle", "example.swift")]`
     fn is_source_begin(begin_line: &String) -> Option<Vec<(String, String)>> {
         let pattern = "+BEGIN_SRC";
-        if !begin_line.to_uppercase().starts_with(pattern) {
+        let line = begin_line.trim_left().trim_left_matches('#');
+        if !line.to_uppercase().starts_with(pattern) {
             return None;
         }
         let mut properties: Vec<(String, String)> = Vec::new();
         let mut previous_entry: Option<String> = None;
-        for entry in begin_line.split(" ") {
+        for entry in line[pattern.len()..].split_whitespace() {
             let entry = entry.to_owned();
-            if entry.to_uppercase() == pattern {
+            if entry.starts_with(":") {
+                // A key without a value
+                if let Some(previous) = previous_entry {
+                    properties.push((previous, "".to_owned()));
+                }
+                previous_entry = Some(entry);
                 continue;
-            };
+            }
             if let Some(previous) = previous_entry {
                 properties.push((previous, entry));
                 previous_entry = None;
                 continue;
             }
-            if entry.starts_with(":") {
-                previous_entry = Some(entry);
-            } else {
-                properties.push((entry, "".to_owned()));
-            }
+            properties.push((entry, "".to_owned()));
+        }
+        if let Some(previous) = previous_entry {
+            properties.push((previous, "".to_owned()));
         }
         return Some(properties);
     }
 
     fn is_source_end(line: &String) -> bool {
-        return line.to_uppercase().starts_with("+END_SRC");
+        return line.trim_left().trim_left_matches('#').to_uppercase().starts_with("+END_SRC");
     }
 
     fn find_line_meta(line: String) -> Option<(String, String)> {
@@ -195,11 +369,11 @@ impl FileInfo {
                 None => return None,
             };
             if (position + 1) == line.len() {
-                return Some((line, "".to_owned()));
+                return Some((normalize_key(&line), "".to_owned()));
             }
             let components = line[(pattern.len() - 1)..].split_at(position);
-            let mut key = components.0.trim().to_owned();
-            let value = components.1.to_owned();
+            let key = normalize_key(components.0);
+            let value = components.1.trim().to_owned();
             return Some((key, value));
         }
         None
@@ -230,6 +404,10 @@ more text
 console.log(undefined);
 +END_SRC
 
+#+BEGIN_SRC rust :noweb :tangle src/main.rs
+fn main() {}
+#+END_SRC
+
 final end.
 ";
     {
@@ -237,6 +415,31 @@ final end.
         output.write(contents.as_bytes());
     }
 
-    let info = FileInfo::new(Path::new(&filename));
+    let info = FileInfo::new(Path::new(&filename)).unwrap();
     info.describe();
+    assert_eq!(info.meta[1].line, Some(2));
+    assert_eq!(info.meta[1].key_name, "title");
+    assert_eq!(info.meta_contents("+TITLE:"), Some("Benedikt"));
+    assert_eq!(info.tags(), vec!["this", "is", "a", "list", "of", "tags"]);
+    assert!(FileInfo::new(Path::new("/tmp/does-not-exist.org")).is_err());
+    assert_eq!(info.sources.len(), 3);
+    assert_eq!(info.sources[0].property(":cba"), Some("a2"));
+    assert_eq!(info.sources[2].language(), Some("rust"));
+    assert_eq!(info.sources[2].property(":tangle"), Some("src/main.rs"));
+    assert_eq!(info.sources[2].line, 20);
+    assert_eq!(info.sources[2].contents, "fn main() {}");
+}
+
+#[test]
+fn test_toc() {
+    let info = |value: &str| FileInfo {
+        meta: vec![FileMeta { key_name: "toc".to_owned(), value: value.to_owned(), line: None }],
+        sources: Vec::new(),
+    };
+    assert!(info("sidebar").has_toc());
+    assert!(!info("sidebar").has_inline_toc());
+    assert!(info("sidebar").metadata_warnings().is_empty());
+    assert!(info("true").has_inline_toc());
+    assert!(!info("false").has_toc());
+    assert_eq!(info("yes").metadata_warnings().len(), 1);
 }
@@ -1,8 +1,8 @@
 extern crate rustbreak;
-mod fileinfo;
-mod cache_db;
+pub mod fileinfo;
+pub mod cache_db;
 mod dune_post;
 mod org_parser;
-mod org_error;
+pub mod org_error;
 
 pub use self::org_parser::OrgParser;
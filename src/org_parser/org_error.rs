@@ -1,9 +1,43 @@
 use std::error::Error;
 use std::fmt;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrgErrorKind {
+    /// The filename doesn't follow `year-month-day-title`
+    BadFilename,
+    /// The post could not be read from disk
+    UnreadableFile,
+    /// There is no renderer for the post or rendering failed
+    RendererFailure,
+    /// The date of the post is not a valid date
+    InvalidDate,
+    /// The `#+key:` lines or the front matter can't be used
+    MalformedMetadata,
+    /// A file the post refers to doesn't exist
+    MissingAsset,
+}
+
 #[derive(Debug)]
 pub struct OrgError {
+    pub kind: OrgErrorKind,
     pub message: String,
+    /// The line in the post file, if the error can be pinned to one
+    pub line: Option<usize>,
+}
+
+impl OrgError {
+    pub fn new<T: Into<String>>(kind: OrgErrorKind, message: T) -> OrgError {
+        OrgError {
+            kind,
+            message: message.into(),
+            line: None,
+        }
+    }
+
+    pub fn line(mut self, line: Option<usize>) -> Self {
+        self.line = line;
+        self
+    }
 }
 
 impl Error for OrgError {
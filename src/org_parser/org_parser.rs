@@ -2,61 +2,107 @@ use self::super::super::dune_post::{DunePost, DunePostTime};
 use std::path::{PathBuf, Path};
 use org_parser::dune_post::*;
 use org_parser::cache_db::CacheDB;
-use std::fs;
+use org_parser::fileinfo::FileInfo;
+use org_parser::org_error::{OrgError, OrgErrorKind};
+use dune_diagnostic::DuneParseResult;
+use traits::DunePostSource;
+use renderer::DuneRenderers;
+use utils::{parallel_map, post_files};
+use std::sync::Arc;
+use chrono::FixedOffset;
 
 pub struct OrgParser {
     folder: PathBuf,
+    /// How many posts are parsed and rendered at the same time
     max_threads: i32,
     org_extension: &'static str,
-    cache_db: CacheDB
+    cache_db: Arc<CacheDB>,
+    renderers: Arc<DuneRenderers>,
+    /// For dates without an offset
+    timezone: FixedOffset
 }
 
 impl OrgParser {
-    pub fn new<T: AsRef<Path>>(folder: T, max_threads: i32) -> OrgParser {
+    pub fn new<T: AsRef<Path>>(folder: T, max_threads: i32, cache_db: Arc<CacheDB>) -> OrgParser {
         OrgParser {
             folder: folder.as_ref().to_owned(),
             max_threads,
             org_extension: "org",
-            cache_db: CacheDB::new("cache.db")
+            cache_db,
+            renderers: Arc::new(DuneRenderers::default()),
+            timezone: FixedOffset::east(0)
         }
     }
 
-    pub fn parse(&self) -> Vec<DunePost> {
-        let mut matches: Vec<DunePost> = Vec::new();
-        let result = fs::read_dir(&self.folder);
-        if let Err(x) = result {
-            return matches;
+    pub fn renderers(mut self, renderers: DuneRenderers) -> Self {
+        self.renderers = Arc::new(renderers);
+        self
+    }
+
+    pub fn timezone(mut self, timezone: FixedOffset) -> Self {
+        self.timezone = timezone;
+        self
+    }
+}
+
+fn parse_post(filename: &str, path: &PathBuf, cache_db: &CacheDB, renderers: &DuneRenderers,
+              timezone: &FixedOffset) -> DuneParseResult {
+    let mut result = DuneParseResult::new();
+    let fileinfo = match FileInfo::new(path) {
+        Ok(n) => n,
+        Err(e) => {
+            result.error(path, e);
+            return result;
         }
-        for entry in result.unwrap() {
-            let path = match entry {
-                Ok(x) => x,
-                Err(_) => continue,
-            };
-            match path.file_name().into_string() {
-                Ok(string) => {
-                    if string.contains(&self.org_extension) {
-                        match DunePost::new(&string, &path.path(), &self.cache_db) {
-                            Ok(blog) => {
-                                matches.push(blog);
-                            }
-                            Err(e) => {
-                                println!("Could not parse {}", string);
-                                println!("Error: {}", e);
-                                continue;
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    println!("Invalid filename: ");
-                    continue;
+    };
+    for warning in fileinfo.warnings() {
+        result.warning(path, warning);
+    }
+    match DunePost::new(filename, path, &fileinfo, cache_db, renderers, timezone) {
+        Ok(post) => result.posts.push(post),
+        Err(e) => result.error(path, e),
+    }
+    result
+}
+
+impl DunePostSource for OrgParser {
+    fn parse(&self) -> DuneParseResult {
+        let mut result = DuneParseResult::new();
+        let mut files: Vec<(String, PathBuf)> = Vec::new();
+        // Posts can be in subfolders, i.e. `2018/` or a bundle with its images
+        let paths = match post_files(&self.folder) {
+            Ok(n) => n,
+            Err(e) => {
+                result.error(&self.folder, OrgError::new(OrgErrorKind::UnreadableFile,
+                                                         format!("Could not read the post folder: {}", e)));
+                return result;
+            }
+        };
+        for path in paths {
+            if path.extension().and_then(|n| n.to_str()) != Some(self.org_extension) {
+                continue;
+            }
+            // The path inside the post folder identifies the post in the cache
+            let relative = path.strip_prefix(&self.folder).unwrap_or(&path).to_str().map(String::from);
+            match relative {
+                Some(filename) => files.push((filename, path)),
+                None => {
+                    // We can't tell whether this is a post at all
+                    result.warning(&path, OrgError::new(OrgErrorKind::BadFilename,
+                                                        "The filename is not valid UTF-8"));
                 }
             };
         }
-        /*matches.sort_by(|a, b| {
-            b.timestamp().cmp(&b.timestamp())
+
+        let cache_db = Arc::clone(&self.cache_db);
+        let renderers = Arc::clone(&self.renderers);
+        let timezone = self.timezone;
+        let results = parallel_map(files, self.max_threads as usize, move |(filename, path)| {
+            parse_post(&filename, &path, &cache_db, &renderers, &timezone)
         });
-        matches.reverse();*/
-        return matches;
+        for post_result in results {
+            result.extend(post_result);
+        }
+        result
     }
 }
@@ -0,0 +1,175 @@
+use assets::find_assets;
+use dune_diagnostic::DuneParseResult;
+use dune_page::{DunePageTemplate, DuneStaticPage};
+use dune_post::DuneMetaValue;
+use markdown_parser::front_matter;
+use org_parser::fileinfo::FileInfo;
+use org_parser::org_error::{OrgError, OrgErrorKind};
+use renderer::DuneRenderers;
+use traits::DunePostSource;
+use utils::{post_files, post_name};
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+use chrono::FixedOffset;
+
+/// Parses the static pages, org or markdown files without a date
+/// in the filename. Pages are few, so they are not cached.
+pub struct PageParser {
+    folder: PathBuf,
+    renderers: DuneRenderers,
+    /// For date values in the metadata
+    timezone: FixedOffset,
+}
+
+impl PageParser {
+    pub fn new<T: AsRef<Path>>(folder: T) -> PageParser {
+        PageParser {
+            folder: folder.as_ref().to_owned(),
+            renderers: DuneRenderers::default(),
+            timezone: FixedOffset::east(0),
+        }
+    }
+
+    pub fn renderers(mut self, renderers: DuneRenderers) -> Self {
+        self.renderers = renderers;
+        self
+    }
+
+    pub fn timezone(mut self, timezone: FixedOffset) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    fn parse_page(&self, path: &Path, result: &mut DuneParseResult) -> Result<DuneStaticPage, OrgError> {
+        let (identifier, fileinfo, contents) = render_file(path, &self.renderers)?;
+        for warning in fileinfo.warnings() {
+            result.warning(path, warning);
+        }
+
+        let template = match fileinfo.meta_entry("template") {
+            Some(entry) => match DunePageTemplate::from_name(&entry.value) {
+                Some(template) => template,
+                None => {
+                    result.warning(path, OrgError::new(
+                        OrgErrorKind::MalformedMetadata,
+                        format!("Unknown template {}, use page or wide", entry.value)).line(entry.line));
+                    DunePageTemplate::Page
+                }
+            },
+            None => DunePageTemplate::Page,
+        };
+
+        let meta = meta_values(&fileinfo, &self.timezone);
+        let enabled = fileinfo.is_enabled() && fileinfo.status().as_ref().map(|n| n.as_str()) != Some("draft");
+        Ok(DuneStaticPage {
+            title: fileinfo.title().unwrap_or(identifier.clone()),
+            identifier: identifier,
+            source: path.to_owned(),
+            assets: find_assets(&contents, path.parent().unwrap_or(Path::new("."))),
+            contents: contents,
+            description: fileinfo.desc(),
+            template: template,
+            enabled: enabled,
+            meta: meta,
+        })
+    }
+}
+
+/// The name, the metadata and the rendered html of a page, an org file
+/// or a markdown file with front matter. Projects are read the same way.
+pub fn render_file(path: &Path, renderers: &DuneRenderers) -> Result<(String, FileInfo, String), OrgError> {
+    let identifier = post_name(path)
+        .ok_or(OrgError::new(OrgErrorKind::BadFilename, format!("Invalid filename {:?}", path)))?;
+    let fileinfo = match path.extension().and_then(|n| n.to_str()) {
+        Some("org") => FileInfo::new(path)?,
+        _ => {
+            let mut contents = String::new();
+            File::open(path)
+                .and_then(|mut file| file.read_to_string(&mut contents))
+                .map_err(|e| OrgError::new(OrgErrorKind::UnreadableFile,
+                                           format!("Could not read file {}: {}", &identifier, e)))?;
+            let meta = match front_matter::split(&contents) {
+                (Some(matter), _) => front_matter::parse(matter)?,
+                (None, _) => Vec::new(),
+            };
+            FileInfo {
+                meta: meta,
+                sources: Vec::new(),
+            }
+        }
+    };
+    let renderer = renderers.renderer_for(path, &fileinfo)
+        .ok_or(OrgError::new(OrgErrorKind::RendererFailure, format!("No renderer for {}", identifier)))?;
+    let contents = renderer.render(path, &fileinfo)
+        .map_err(|e| OrgError::new(OrgErrorKind::RendererFailure,
+                                   format!("{} failed: {}", renderer.name(), e)))?;
+    Ok((identifier, fileinfo, contents))
+}
+
+/// The typed metadata values, the first entry wins if a key is used more than once
+pub fn meta_values(fileinfo: &FileInfo, timezone: &FixedOffset) -> BTreeMap<String, DuneMetaValue> {
+    let mut meta: BTreeMap<String, DuneMetaValue> = BTreeMap::new();
+    for entry in &fileinfo.meta {
+        meta.entry(entry.key_name.clone())
+            .or_insert_with(|| DuneMetaValue::new(&entry.key_name, &entry.value, timezone));
+    }
+    meta
+}
+
+/// The files of a page or project folder, org and markdown
+pub fn page_files(folder: &Path) -> Result<Vec<PathBuf>, OrgError> {
+    let paths = post_files(folder)
+        .map_err(|e| OrgError::new(OrgErrorKind::UnreadableFile, format!("Could not read {}: {}", folder.display(), e)))?;
+    Ok(paths.into_iter()
+       .filter(|path| match path.extension().and_then(|n| n.to_str()) {
+           Some("org") | Some("md") | Some("markdown") => true,
+           _ => false,
+       })
+       .collect())
+}
+
+impl DunePostSource for PageParser {
+    fn parse(&self) -> DuneParseResult {
+        let mut result = DuneParseResult::new();
+        // Most blogs don't have pages
+        if !self.folder.is_dir() {
+            return result;
+        }
+        let paths = match page_files(&self.folder) {
+            Ok(n) => n,
+            Err(e) => {
+                result.error(&self.folder, e);
+                return result;
+            }
+        };
+        for path in paths {
+            match self.parse_page(&path, &mut result) {
+                Ok(page) => result.pages.push(page),
+                Err(e) => result.error(&path, e),
+            }
+        }
+        result
+    }
+}
+
+#[test]
+fn test_parse_pages() {
+    use std::fs;
+    let folder = ::std::env::temp_dir().join("wanderdune-test-pages");
+    fs::create_dir_all(&folder).unwrap();
+    File::create(folder.join("about.org")).unwrap()
+        .write_all(b"#+title: About\n#+menu: 1\n#+template: wide\n\nHello\n").unwrap();
+    File::create(folder.join("talks.md")).unwrap()
+        .write_all(b"---\ntitle: Talks\ntemplate: slides\n---\n\nTalks\n").unwrap();
+    let result = PageParser::new(&folder).parse();
+    assert_eq!(result.pages.len(), 2);
+    assert_eq!(result.pages[0].identifier, "about");
+    assert_eq!(result.pages[0].template, DunePageTemplate::Wide);
+    assert_eq!(result.pages[0].menu(), Some(1));
+    assert_eq!(result.pages[1].template, DunePageTemplate::Page);
+    assert_eq!(result.diagnostics.len(), 1);
+}
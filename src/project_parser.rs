@@ -0,0 +1,137 @@
+use assets::{find_asset, find_assets};
+use dune_base::{DuneProject, DuneProjectStatus};
+use dune_diagnostic::DuneParseResult;
+use org_parser::org_error::{OrgError, OrgErrorKind};
+use page_parser::{page_files, render_file};
+use renderer::DuneRenderers;
+use traits::DunePostSource;
+
+use std::path::{Path, PathBuf};
+
+/// Parses the projects, org or markdown files with
+/// `name`, `summary`, `repository`, `status`, `tags` and `screenshot`
+pub struct ProjectParser {
+    folder: PathBuf,
+    renderers: DuneRenderers,
+}
+
+impl ProjectParser {
+    pub fn new<T: AsRef<Path>>(folder: T) -> ProjectParser {
+        ProjectParser {
+            folder: folder.as_ref().to_owned(),
+            renderers: DuneRenderers::default(),
+        }
+    }
+
+    pub fn renderers(mut self, renderers: DuneRenderers) -> Self {
+        self.renderers = renderers;
+        self
+    }
+
+    fn parse_project(&self, path: &Path, result: &mut DuneParseResult) -> Result<DuneProject, OrgError> {
+        let (identifier, fileinfo, contents) = render_file(path, &self.renderers)?;
+        for warning in fileinfo.metadata_warnings() {
+            result.warning(path, warning);
+        }
+        let folder = path.parent().unwrap_or(Path::new("."));
+
+        let status = match fileinfo.meta_entry("status") {
+            Some(entry) => match DuneProjectStatus::from_name(&entry.value) {
+                Some(status) => status,
+                None => {
+                    result.warning(path, OrgError::new(
+                        OrgErrorKind::MalformedMetadata,
+                        format!("Expected active, maintained or archived for status, found '{}'", entry.value))
+                                   .line(entry.line));
+                    DuneProjectStatus::Active
+                }
+            },
+            None => DuneProjectStatus::Active,
+        };
+
+        let mut assets = find_assets(&contents, folder);
+        let screenshot = match fileinfo.meta_entry("screenshot") {
+            Some(entry) if !entry.value.is_empty() => {
+                let value = entry.value.clone();
+                if value.contains("://") || value.starts_with('/') {
+                    Some(value)
+                } else if let Some(asset) = find_asset(&value, folder) {
+                    assets.push(asset);
+                    Some(value)
+                } else {
+                    result.warning(path, OrgError::new(
+                        OrgErrorKind::MissingAsset,
+                        format!("The screenshot {} is not next to the project", value)).line(entry.line));
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        let name = match fileinfo.meta_contents("name") {
+            Some(n) if !n.trim().is_empty() => n.trim().to_owned(),
+            _ => fileinfo.title().unwrap_or(identifier.clone()),
+        };
+        let summary = match fileinfo.meta_contents("summary") {
+            Some(n) => n.trim().to_owned(),
+            None => fileinfo.desc(),
+        };
+        let repository = match fileinfo.meta_contents("repository") {
+            Some(n) if !n.trim().is_empty() => Some(n.trim().to_owned()),
+            _ => None,
+        };
+        Ok(DuneProject {
+            identifier: identifier,
+            source: path.to_owned(),
+            name: name,
+            summary: summary,
+            repository: repository,
+            status: status,
+            tags: fileinfo.tags(),
+            screenshot: screenshot,
+            contents: contents,
+            assets: assets,
+        })
+    }
+}
+
+impl DunePostSource for ProjectParser {
+    fn parse(&self) -> DuneParseResult {
+        let mut result = DuneParseResult::new();
+        // Most blogs don't have projects
+        if !self.folder.is_dir() {
+            return result;
+        }
+        let paths = match page_files(&self.folder) {
+            Ok(n) => n,
+            Err(e) => {
+                result.error(&self.folder, e);
+                return result;
+            }
+        };
+        for path in paths {
+            match self.parse_project(&path, &mut result) {
+                Ok(project) => result.projects.push(project),
+                Err(e) => result.error(&path, e),
+            }
+        }
+        result
+    }
+}
+
+#[test]
+fn test_parse_projects() {
+    use std::fs::{self, File};
+    use std::io::prelude::*;
+    let folder = ::std::env::temp_dir().join("wanderdune-test-projects");
+    fs::create_dir_all(&folder).unwrap();
+    File::create(folder.join("wanderduene.org")).unwrap()
+        .write_all(b"#+name: Wanderduene\n#+summary: A static blog generator\n#+status: archived\n\
+                     #+tags: rust\n#+screenshot: missing.png\n\nIn Rust\n").unwrap();
+    let result = ProjectParser::new(&folder).parse();
+    assert_eq!(result.projects.len(), 1);
+    assert_eq!(result.projects[0].name, "Wanderduene");
+    assert_eq!(result.projects[0].status, DuneProjectStatus::Archived);
+    assert_eq!(result.projects[0].screenshot, None);
+    assert_eq!(result.diagnostics[0].kind(), OrgErrorKind::MissingAsset);
+}
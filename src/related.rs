@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use dune_post::DunePost;
+use utils::strip_tags;
+
+/// A shared tag counts more than a shared keyword
+const TAG_WEIGHT: f64 = 3.0;
+const KEYWORD_WEIGHT: f64 = 2.0;
+/// The text similarity is between 0 and 1
+const TEXT_WEIGHT: f64 = 4.0;
+
+/// For every post, the positions of the `count` most related listed posts
+/// in the same language, best first. Posts without `#+language:` are in
+/// `language`. Tags in `excluded` (i.e. `blog`) don't count.
+pub fn related(posts: &[DunePost], count: usize, excluded: &[String], language: &str) -> Vec<Vec<usize>> {
+    let vectors = tf_idf(posts);
+    let language_of = |post: &DunePost| post.language.as_ref().map(|n| n.as_str()).unwrap_or(language).to_owned();
+    let languages: Vec<String> = posts.iter().map(|post| language_of(post)).collect();
+    posts.iter().enumerate().map(|(position, post)| {
+        let mut scores: Vec<(f64, usize)> = posts.iter().enumerate()
+            .filter(|&(other_position, other)| other_position != position && other.is_listed()
+                    && languages[other_position] == languages[position])
+            .map(|(other_position, other)| {
+                let tags = shared(&post.tags, &other.tags, excluded);
+                let keywords = shared(&post.keywords, &other.keywords, &[]);
+                let text = cosine(&vectors[position], &vectors[other_position]);
+                (TAG_WEIGHT * tags + KEYWORD_WEIGHT * keywords + TEXT_WEIGHT * text, other_position)
+            })
+            .filter(|&(score, _)| score > 0.0)
+            .collect();
+        // Equal scores go to the newer post
+        scores.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(::std::cmp::Ordering::Equal)
+                       .then(posts[b.1].cmp(&posts[a.1])));
+        scores.into_iter().take(count).map(|(_, position)| position).collect()
+    }).collect()
+}
+
+fn shared(a: &[String], b: &[String], excluded: &[String]) -> f64 {
+    a.iter().filter(|entry| b.contains(entry) && !excluded.contains(entry)).count() as f64
+}
+
+fn words(html: &str) -> Vec<String> {
+    strip_tags(html).to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        // `a`, `is`, `in` are in every post
+        .filter(|word| word.chars().count() > 2)
+        .map(|word| word.to_owned())
+        .collect()
+}
+
+/// The normalized TF-IDF vectors of the contents of the posts
+fn tf_idf(posts: &[DunePost]) -> Vec<HashMap<String, f64>> {
+    let frequencies: Vec<HashMap<String, f64>> = posts.iter().map(|post| {
+        let mut frequency: HashMap<String, f64> = HashMap::new();
+        for word in words(&post.contents) {
+            *frequency.entry(word).or_insert(0.0) += 1.0;
+        }
+        frequency
+    }).collect();
+    let mut documents: HashMap<&str, f64> = HashMap::new();
+    for frequency in &frequencies {
+        for word in frequency.keys() {
+            *documents.entry(word).or_insert(0.0) += 1.0;
+        }
+    }
+    let total = posts.len() as f64;
+    frequencies.iter().map(|frequency| {
+        let mut vector: HashMap<String, f64> = frequency.iter()
+            .map(|(word, count)| (word.clone(), count * (total / documents[word.as_str()]).ln()))
+            .collect();
+        let length = vector.values().map(|value| value * value).sum::<f64>().sqrt();
+        if length > 0.0 {
+            for value in vector.values_mut() {
+                *value /= length;
+            }
+        }
+        vector
+    }).collect()
+}
+
+fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    a.iter().filter_map(|(word, value)| b.get(word).map(|other| value * other)).sum()
+}
+
+#[test]
+fn test_related() {
+    let post = |name: &str, tags: &str, contents: &str| DunePost::test_post(name, &[("tags", tags)], contents);
+    let mut posts = vec![post("2018-01-10-enums", "swift blog", "<p>Enums with associated values</p>"),
+                         post("2018-02-10-patterns", "swift", "<p>Pattern matching on enums with associated values</p>"),
+                         post("2018-03-10-cooking", "blog", "<p>Bread and butter</p>"),
+                         post("2018-04-10-closures", "rust", "<p>Closures capture their environment</p>")];
+    // `#+language: en` on an English blog is the same as no language
+    posts[1].language = Some("en".to_owned());
+    let related = related(&posts, 2, &["blog".to_owned()], "en");
+    assert_eq!(related[0], vec![1]);
+    assert_eq!(related[3], Vec::<usize>::new());
+}
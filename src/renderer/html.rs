@@ -0,0 +1,29 @@
+use markdown_parser::front_matter;
+use org_parser::fileinfo::FileInfo;
+use renderer::{read_source, DuneRenderer};
+
+use std::error::Error;
+use std::path::Path;
+
+/// Html posts are used as they are, only the front matter is removed
+pub struct HTMLRenderer;
+
+impl DuneRenderer for HTMLRenderer {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["html"]
+    }
+
+    fn fingerprint(&self, _info: &FileInfo) -> String {
+        "html".to_owned()
+    }
+
+    fn render(&self, path: &Path, _info: &FileInfo) -> Result<String, Box<Error>> {
+        let contents = read_source(path)?;
+        let (_, body) = front_matter::split(&contents);
+        Ok(body.to_owned())
+    }
+}
@@ -0,0 +1,38 @@
+use pulldown_cmark::{html, Parser, OPTION_ENABLE_FOOTNOTES, OPTION_ENABLE_TABLES};
+
+use markdown_parser::front_matter;
+use org_parser::fileinfo::FileInfo;
+use renderer::{read_source, DuneRenderer};
+use utils::escape_html;
+
+use std::error::Error;
+use std::path::Path;
+
+pub struct MarkdownRenderer;
+
+impl DuneRenderer for MarkdownRenderer {
+    fn name(&self) -> &str {
+        "markdown"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["markdown", "md"]
+    }
+
+    fn fingerprint(&self, _info: &FileInfo) -> String {
+        format!("markdown {}", env!("CARGO_PKG_VERSION"))
+    }
+
+    fn render(&self, path: &Path, info: &FileInfo) -> Result<String, Box<Error>> {
+        let contents = read_source(path)?;
+        let (_, body) = front_matter::split(&contents);
+        let mut output = String::new();
+        // Same as the pandoc template
+        if let Some(title) = info.title() {
+            output.push_str(&format!("<h1 class=\"title\">{}</h1>\n", escape_html(&title)));
+        }
+        let parser = Parser::new_ext(body, OPTION_ENABLE_TABLES | OPTION_ENABLE_FOOTNOTES);
+        html::push_html(&mut output, parser);
+        Ok(output)
+    }
+}
@@ -0,0 +1,110 @@
+mod org;
+mod pandoc;
+mod markdown;
+mod html;
+
+pub use self::org::OrgRenderer;
+pub use self::pandoc::PandocRenderer;
+pub use self::markdown::MarkdownRenderer;
+pub use self::html::HTMLRenderer;
+
+use org_parser::fileinfo::FileInfo;
+
+use std::error::Error;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+
+/// Turns the source file of a post into html.
+/// Renderers are shared between the parser threads.
+pub trait DuneRenderer: Send + Sync {
+    /// The name that selects this renderer via `#+renderer:` in org files
+    /// or `renderer:` in the front matter
+    fn name(&self) -> &str;
+    /// The file extensions this renderer is used for if there's no override
+    fn extensions(&self) -> &[&str];
+    /// Identifies the renderer and the settings that change its output for a post.
+    /// Cached html is only reused if the fingerprint is unchanged.
+    fn fingerprint(&self, info: &FileInfo) -> String;
+    fn render(&self, path: &Path, info: &FileInfo) -> Result<String, Box<Error>>;
+}
+
+/// All the renderers that are available for a build
+pub struct DuneRenderers {
+    renderers: Vec<Box<DuneRenderer>>,
+}
+
+impl DuneRenderers {
+    pub fn new() -> DuneRenderers {
+        DuneRenderers {
+            renderers: Vec::new(),
+        }
+    }
+
+    /// The native org renderer, markdown, html passthrough and pandoc.
+    /// Pandoc is only used if a post asks for it explicitly.
+    pub fn default() -> DuneRenderers {
+        DuneRenderers::new()
+            .register(PandocRenderer::new(Vec::new()))
+            .register(HTMLRenderer)
+            .register(MarkdownRenderer)
+            .register(OrgRenderer)
+    }
+
+    /// Renderers that are registered later take precedence, so that
+    /// i.e. `PandocRenderer::new(vec!["org"])` replaces the org renderer.
+    pub fn register<R: DuneRenderer + 'static>(mut self, renderer: R) -> Self {
+        self.renderers.insert(0, Box::new(renderer));
+        self
+    }
+
+    /// Find the renderer for a file. The override in the metadata wins
+    /// over the file extension.
+    pub fn renderer_for(&self, path: &Path, info: &FileInfo) -> Option<&DuneRenderer> {
+        if let Some(name) = info.renderer() {
+            return self.renderers.iter()
+                .find(|renderer| renderer.name() == name)
+                .map(|renderer| renderer.as_ref());
+        }
+        let extension = path.extension().and_then(|n| n.to_str())?;
+        self.renderers.iter()
+            .find(|renderer| renderer.extensions().contains(&extension))
+            .map(|renderer| renderer.as_ref())
+    }
+}
+
+fn read_source(path: &Path) -> Result<String, Box<Error>> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+#[test]
+fn test_renderer_for() {
+    use org_parser::fileinfo::FileMeta;
+
+    struct MockRenderer;
+    impl DuneRenderer for MockRenderer {
+        fn name(&self) -> &str { "mock" }
+        fn extensions(&self) -> &[&str] { &["org"] }
+        fn fingerprint(&self, _info: &FileInfo) -> String { "mock".to_owned() }
+        fn render(&self, _path: &Path, _info: &FileInfo) -> Result<String, Box<Error>> {
+            Ok("mocked".to_owned())
+        }
+    }
+
+    let renderers = DuneRenderers::default();
+    let info = FileInfo { meta: Vec::new(), sources: Vec::new() };
+    assert_eq!(renderers.renderer_for(Path::new("a.org"), &info).unwrap().name(), "org");
+    assert_eq!(renderers.renderer_for(Path::new("a.md"), &info).unwrap().name(), "markdown");
+    assert!(renderers.renderer_for(Path::new("a.txt"), &info).is_none());
+
+    let renderers = renderers.register(MockRenderer);
+    assert_eq!(renderers.renderer_for(Path::new("a.org"), &info).unwrap().name(), "mock");
+
+    let info = FileInfo {
+        meta: vec![FileMeta { key_name: "renderer".to_owned(), value: " pandoc".to_owned(), line: None }],
+        sources: Vec::new(),
+    };
+    assert_eq!(renderers.renderer_for(Path::new("a.org"), &info).unwrap().name(), "pandoc");
+}
@@ -0,0 +1,676 @@
+use org_parser::fileinfo::FileInfo;
+use renderer::{read_source, DuneRenderer};
+use utils::escape_html;
+
+use std::cmp;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+/// The built-in org renderer
+pub struct OrgRenderer;
+
+impl DuneRenderer for OrgRenderer {
+    fn name(&self) -> &str {
+        "org"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["org"]
+    }
+
+    fn fingerprint(&self, info: &FileInfo) -> String {
+        format!("org {} toc {}", env!("CARGO_PKG_VERSION"), info.has_inline_toc())
+    }
+
+    fn render(&self, path: &Path, info: &FileInfo) -> Result<String, Box<Error>> {
+        let contents = read_source(path)?;
+        Ok(render_org(&contents, info.has_inline_toc()))
+    }
+}
+
+/// Renders an org document into html without shelling out to pandoc.
+/// This only understands the subset of org-mode that the posts use:
+/// Headings, emphasis, links, lists, tables, blocks and footnotes.
+pub fn render_org(contents: &str, has_toc: bool) -> String {
+    let mut renderer = OrgDocument::new();
+    let lines: Vec<&str> = contents.lines().collect();
+    let body = renderer.blocks(&lines, false);
+
+    let mut html = String::new();
+    if let Some(ref title) = renderer.title {
+        html.push_str(&format!("<h1 class=\"title\">{}</h1>\n", escape_html(title)));
+    }
+    if has_toc && !renderer.headings.is_empty() {
+        html.push_str(&renderer.toc());
+    }
+    html.push_str(&body);
+    html.push_str(&renderer.footnotes());
+    html
+}
+
+struct OrgHeading {
+    level: usize,
+    html: String,
+    anchor: String,
+}
+
+struct OrgDocument {
+    title: Option<String>,
+    headings: Vec<OrgHeading>,
+    /// The numbering of the current heading, i.e. [2, 1] for `sec-2-1`
+    section: Vec<usize>,
+    /// Footnote labels in the order in which they are referenced
+    footnote_refs: Vec<String>,
+    footnote_defs: HashMap<String, String>,
+    anonymous_footnotes: usize,
+}
+
+impl OrgDocument {
+    fn new() -> OrgDocument {
+        OrgDocument {
+            title: None,
+            headings: Vec::new(),
+            section: Vec::new(),
+            footnote_refs: Vec::new(),
+            footnote_defs: HashMap::new(),
+            anonymous_footnotes: 0,
+        }
+    }
+
+    /// Render a number of lines as block level elements. `tight` paragraphs
+    /// (i.e. in a list item without blank lines) are not wrapped in `<p>`.
+    fn blocks(&mut self, lines: &[&str], tight: bool) -> String {
+        let mut out = String::new();
+        let mut index = 0;
+        while index < lines.len() {
+            let line = lines[index];
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                index += 1;
+                continue;
+            }
+            if let Some(name) = block_begin(trimmed) {
+                let end = block_end(lines, index + 1, &name);
+                let inner = &lines[(index + 1)..end];
+                out.push_str(&self.block(&name, trimmed, inner));
+                index = end + 1;
+                continue;
+            }
+            if trimmed.starts_with("#+") {
+                self.keyword(trimmed, &mut out);
+                index += 1;
+                continue;
+            }
+            if trimmed == "#" || trimmed.starts_with("# ") {
+                index += 1;
+                continue;
+            }
+            if let Some((level, text)) = heading(line) {
+                out.push_str(&self.heading(level, text));
+                index += 1;
+                continue;
+            }
+            if trimmed.len() >= 5 && trimmed.chars().all(|c| c == '-') {
+                out.push_str("<hr/>\n");
+                index += 1;
+                continue;
+            }
+            if trimmed.starts_with('|') {
+                let end = take_while(lines, index, |l| l.trim().starts_with('|'));
+                out.push_str(&self.table(&lines[index..end]));
+                index = end;
+                continue;
+            }
+            if trimmed == ":" || trimmed.starts_with(": ") {
+                let end = take_while(lines, index, |l| {
+                    let l = l.trim();
+                    l == ":" || l.starts_with(": ")
+                });
+                let text: Vec<String> = lines[index..end].iter()
+                    .map(|l| escape_html(l.trim().trim_left_matches(':').trim_left_matches(' ')))
+                    .collect();
+                out.push_str(&format!("<pre class=\"example\">{}</pre>\n", text.join("\n")));
+                index = end;
+                continue;
+            }
+            if let Some((label, text)) = footnote_definition(line) {
+                let end = take_while(lines, index + 1, |l| {
+                    !l.trim().is_empty() && footnote_definition(l).is_none() && heading(l).is_none()
+                });
+                let mut definition = vec![text];
+                definition.extend_from_slice(&lines[(index + 1)..end]);
+                let html = self.inline(&definition.join("\n"));
+                self.footnote_defs.insert(label, html);
+                index = end;
+                continue;
+            }
+            if let Some(item) = list_item(line) {
+                index = self.list(lines, index, item, &mut out);
+                continue;
+            }
+            let end = take_while(lines, index + 1, |l| !ends_paragraph(l));
+            let text: Vec<&str> = lines[index..end].iter().map(|l| l.trim()).collect();
+            let html = self.inline(&text.join("\n"));
+            match tight {
+                true => out.push_str(&format!("{}\n", html)),
+                false => out.push_str(&format!("<p>{}</p>\n", html)),
+            }
+            index = end;
+        }
+        out
+    }
+
+    fn keyword(&mut self, line: &str, out: &mut String) {
+        // `#+more` ends the excerpt of the post
+        if line[2..].trim_right_matches(':').eq_ignore_ascii_case("more") {
+            out.push_str("<!--more-->\n");
+            return;
+        }
+        let (key, value) = match line[2..].find(':') {
+            Some(n) => (line[2..(n + 2)].to_lowercase(), line[(n + 3)..].trim()),
+            None => return,
+        };
+        match key.as_ref() {
+            "title" if self.title.is_none() => self.title = Some(value.to_owned()),
+            "html" => {
+                out.push_str(value);
+                out.push('\n');
+            }
+            _ => (),
+        }
+    }
+
+    fn heading(&mut self, level: usize, text: &str) -> String {
+        self.section.truncate(level);
+        while self.section.len() < level {
+            self.section.push(0);
+        }
+        self.section[level - 1] += 1;
+        let numbers: Vec<String> = self.section.iter().map(|n| n.to_string()).collect();
+        let anchor = format!("sec-{}", numbers.join("-"));
+        let html = self.inline(text);
+        let tag = cmp::min(level, 6);
+        self.headings.push(OrgHeading {
+            level: level,
+            html: html.clone(),
+            anchor: anchor.clone(),
+        });
+        format!("<h{} id=\"{}\">{}</h{}>\n", tag, anchor, html, tag)
+    }
+
+    fn block(&mut self, name: &str, begin: &str, lines: &[&str]) -> String {
+        let parameters: Vec<&str> = begin.split_whitespace().skip(1).collect();
+        match name {
+            "SRC" => {
+                if parameters.windows(2).any(|p| p[0] == ":exports" && p[1] == "none") {
+                    return String::new();
+                }
+                let language = match parameters.first() {
+                    Some(n) if !n.starts_with(':') => n.to_lowercase(),
+                    _ => String::new(),
+                };
+                let code: Vec<String> = lines.iter().map(|l| escape_html(unescape_line(l))).collect();
+                format!("<pre class=\"sourceCode {}\"><code class=\"sourceCode {}\">{}</code></pre>\n",
+                        language, language, code.join("\n"))
+            }
+            "EXAMPLE" => {
+                let code: Vec<String> = lines.iter().map(|l| escape_html(unescape_line(l))).collect();
+                format!("<pre class=\"example\">{}</pre>\n", code.join("\n"))
+            }
+            "HTML" => format!("{}\n", lines.join("\n")),
+            "EXPORT" => match parameters.first() {
+                Some(n) if n.to_lowercase() == "html" => format!("{}\n", lines.join("\n")),
+                _ => String::new(),
+            },
+            "QUOTE" => format!("<blockquote>\n{}</blockquote>\n", self.blocks(lines, false)),
+            "VERSE" => {
+                let verses: Vec<String> = lines.iter().map(|l| self.inline(l.trim())).collect();
+                format!("<p class=\"verse\">{}</p>\n", verses.join("<br/>\n"))
+            }
+            _ => format!("<div class=\"{}\">\n{}</div>\n", name.to_lowercase(), self.blocks(lines, false)),
+        }
+    }
+
+    fn table(&mut self, lines: &[&str]) -> String {
+        // `None` is a separator row like `|---+---|`
+        let rows: Vec<Option<Vec<&str>>> = lines.iter().map(|line| {
+            let line = line.trim();
+            if line.starts_with("|-") {
+                return None;
+            }
+            let line = line.trim_left_matches('|');
+            let line = if line.ends_with('|') { &line[..(line.len() - 1)] } else { line };
+            Some(line.split('|').map(|cell| cell.trim()).collect())
+        }).collect();
+        // Everything above the first separator is the header, unless the separator is the last row
+        let header_rows = match rows.iter().position(|row| row.is_none()) {
+            Some(n) if n > 0 && rows[n..].iter().any(|row| row.is_some()) => n,
+            _ => 0,
+        };
+        let mut out = String::from("<table>\n");
+        if header_rows > 0 {
+            out.push_str("<thead>\n");
+            for row in rows[..header_rows].iter().filter_map(|row| row.as_ref()) {
+                out.push_str(&self.table_row(row, "th"));
+            }
+            out.push_str("</thead>\n");
+        }
+        out.push_str("<tbody>\n");
+        for row in rows[header_rows..].iter().filter_map(|row| row.as_ref()) {
+            out.push_str(&self.table_row(row, "td"));
+        }
+        out.push_str("</tbody>\n</table>\n");
+        out
+    }
+
+    fn table_row(&mut self, cells: &[&str], tag: &str) -> String {
+        let cells: Vec<String> = cells.iter()
+            .map(|cell| format!("<{}>{}</{}>", tag, self.inline(cell), tag))
+            .collect();
+        format!("<tr>{}</tr>\n", cells.join(""))
+    }
+
+    /// Render the list that starts at `start` and return the index of the first
+    /// line after the list.
+    fn list(&mut self, lines: &[&str], start: usize, first: ListItem, out: &mut String) -> usize {
+        let tag = if first.ordered { "ol" } else { "ul" };
+        out.push_str(&format!("<{}>\n", tag));
+        let mut index = start;
+        while index < lines.len() {
+            let item = match list_item(lines[index]) {
+                Some(n) if n.indent == first.indent && n.ordered == first.ordered => n,
+                _ => break,
+            };
+            let mut contents: Vec<&str> = vec![&lines[index][item.offset..]];
+            let mut blank_lines = 0;
+            index += 1;
+            while index < lines.len() {
+                let line = lines[index];
+                if line.trim().is_empty() {
+                    blank_lines += 1;
+                    // two blank lines end the list
+                    if blank_lines == 2 {
+                        break;
+                    }
+                    contents.push("");
+                    index += 1;
+                    continue;
+                }
+                if indentation(line) <= item.indent {
+                    break;
+                }
+                blank_lines = 0;
+                let dedent = cmp::min(indentation(line), item.offset);
+                contents.push(&line[dedent..]);
+                index += 1;
+            }
+            while contents.last() == Some(&"") {
+                contents.pop();
+            }
+            let tight = !contents.contains(&"");
+            out.push_str(&format!("<li>{}</li>\n", self.blocks(&contents, tight).trim_right()));
+            if blank_lines == 2 {
+                break;
+            }
+        }
+        out.push_str(&format!("</{}>\n", tag));
+        index
+    }
+
+    fn inline(&mut self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::new();
+        let mut index = 0;
+        while index < chars.len() {
+            let c = chars[index];
+            if c == '[' {
+                if let Some(end) = closing_bracket(&chars, index) {
+                    let inner: String = chars[(index + 1)..end].iter().collect();
+                    if let Some(html) = self.bracket(&inner) {
+                        out.push_str(&html);
+                        index = end + 1;
+                        continue;
+                    }
+                }
+            }
+            if c == '@' && starts_with(&chars, index, "@@html:") {
+                let start = index + 7;
+                if let Some(end) = find(&chars, start, "@@") {
+                    out.extend(chars[start..end].iter());
+                    index = end + 2;
+                    continue;
+                }
+            }
+            if "*/_=~+".contains(c) && can_open(&chars, index) {
+                if let Some(end) = find_closing(&chars, index) {
+                    let inner: String = chars[(index + 1)..end].iter().collect();
+                    out.push_str(&self.emphasis(c, &inner));
+                    index = end + 1;
+                    continue;
+                }
+            }
+            if c == '\\' && starts_with(&chars, index, "\\\\")
+                && chars[(index + 2)..].iter().take_while(|c| **c != '\n').all(|c| c.is_whitespace()) {
+                out.push_str("<br/>");
+                index += 2;
+                continue;
+            }
+            out.push_str(&escape_html(&c.to_string()));
+            index += 1;
+        }
+        out
+    }
+
+    fn emphasis(&mut self, marker: char, inner: &str) -> String {
+        match marker {
+            '=' | '~' => format!("<code>{}</code>", escape_html(inner)),
+            '*' => format!("<strong>{}</strong>", self.inline(inner)),
+            '/' => format!("<em>{}</em>", self.inline(inner)),
+            '_' => format!("<span class=\"underline\">{}</span>", self.inline(inner)),
+            _ => format!("<del>{}</del>", self.inline(inner)),
+        }
+    }
+
+    /// Links (`[[target][description]]`) and footnotes (`[fn:label]`, `[fn:: inline]`).
+    /// Returns `None` if the brackets are just text.
+    fn bracket(&mut self, inner: &str) -> Option<String> {
+        if inner.starts_with('[') && inner.ends_with(']') {
+            let target_end = inner.find(']')?;
+            let target = &inner[1..target_end];
+            let rest = &inner[(target_end + 1)..];
+            return match rest.len() {
+                0 => Some(self.link(target, None)),
+                _ if rest.starts_with('[') && rest.ends_with(']') => {
+                    Some(self.link(target, Some(&rest[1..(rest.len() - 1)])))
+                }
+                _ => None,
+            };
+        }
+        if inner.starts_with("fn:") {
+            let footnote = &inner[3..];
+            let label = match footnote.find(':') {
+                Some(n) => {
+                    let label = match &footnote[..n] {
+                        "" => {
+                            self.anonymous_footnotes += 1;
+                            format!("anonymous-{}", self.anonymous_footnotes)
+                        }
+                        label => label.to_owned(),
+                    };
+                    let html = self.inline(footnote[(n + 1)..].trim());
+                    self.footnote_defs.insert(label.clone(), html);
+                    label
+                }
+                None => footnote.to_owned(),
+            };
+            return Some(self.footnote_reference(label));
+        }
+        None
+    }
+
+    fn link(&mut self, target: &str, description: Option<&str>) -> String {
+        let url = if target.starts_with("file:") { &target[5..] } else { target };
+        match description {
+            Some(description) if is_image(description) => {
+                format!("<a href=\"{}\"><img src=\"{}\" alt=\"\"/></a>", escape_html(url), escape_html(description))
+            }
+            Some(description) => format!("<a href=\"{}\">{}</a>", escape_html(url), self.inline(description)),
+            None if is_image(url) => format!("<img src=\"{}\" alt=\"\"/>", escape_html(url)),
+            None => format!("<a href=\"{}\">{}</a>", escape_html(url), escape_html(url)),
+        }
+    }
+
+    fn footnote_reference(&mut self, label: String) -> String {
+        let number = match self.footnote_refs.iter().position(|l| l == &label) {
+            Some(n) => n + 1,
+            None => {
+                self.footnote_refs.push(label);
+                self.footnote_refs.len()
+            }
+        };
+        format!("<sup><a href=\"#fn{}\" class=\"footnoteRef\" id=\"fnref{}\">{}</a></sup>", number, number, number)
+    }
+
+    fn footnotes(&self) -> String {
+        if self.footnote_refs.is_empty() {
+            return String::new();
+        }
+        let mut out = String::from("<section class=\"footnotes\">\n<hr/>\n<ol>\n");
+        for (index, label) in self.footnote_refs.iter().enumerate() {
+            let number = index + 1;
+            let html = self.footnote_defs.get(label).map_or("", |n| n.as_str());
+            out.push_str(&format!("<li id=\"fn{}\">{} <a href=\"#fnref{}\">↩</a></li>\n", number, html, number));
+        }
+        out.push_str("</ol>\n</section>\n");
+        out
+    }
+
+    fn toc(&self) -> String {
+        let mut out = String::from("<nav id=\"TOC\">\n");
+        // the levels of the currently open lists
+        let mut open: Vec<usize> = Vec::new();
+        for heading in self.headings.iter() {
+            match open.last().cloned() {
+                Some(last) if heading.level <= last => {
+                    out.push_str("</li>\n");
+                    while open.len() > 1 && open[open.len() - 2] >= heading.level {
+                        open.pop();
+                        out.push_str("</ul>\n</li>\n");
+                    }
+                    // For `*`, `***`, `**` the `**` is in the list of the `***`
+                    if let Some(last) = open.last_mut() {
+                        if heading.level < *last {
+                            *last = heading.level;
+                        }
+                    }
+                }
+                _ => {
+                    open.push(heading.level);
+                    out.push_str("<ul>\n");
+                }
+            }
+            out.push_str(&format!("<li><a href=\"#{}\">{}</a>", heading.anchor, heading.html));
+        }
+        while open.pop().is_some() {
+            out.push_str("</li>\n</ul>\n");
+        }
+        out.push_str("</nav>\n");
+        out
+    }
+}
+
+struct ListItem {
+    indent: usize,
+    /// Where the contents of the item start
+    offset: usize,
+    ordered: bool,
+}
+
+fn list_item(line: &str) -> Option<ListItem> {
+    let indent = indentation(line);
+    let rest = &line[indent..];
+    // A `*` in the first column is a heading, not a bullet
+    let bullets: &[&str] = if indent > 0 { &["- ", "+ ", "* "] } else { &["- ", "+ "] };
+    if bullets.iter().any(|b| rest.starts_with(b)) {
+        return Some(ListItem { indent: indent, offset: indent + 2, ordered: false });
+    }
+    let digits = rest.chars().take_while(|c| c.is_digit(10)).count();
+    if digits > 0 && (rest[digits..].starts_with(". ") || rest[digits..].starts_with(") ")) {
+        return Some(ListItem { indent: indent, offset: indent + digits + 2, ordered: true });
+    }
+    None
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    if level == 0 || !line[level..].starts_with(' ') {
+        return None;
+    }
+    let text = line[level..].trim();
+    // Drop trailing org tags like `:noexport:`
+    let text = match text.rfind(char::is_whitespace) {
+        Some(n) if is_tag_list(&text[(n + 1)..]) => text[..n].trim(),
+        _ => text,
+    };
+    Some((level, text))
+}
+
+fn is_tag_list(text: &str) -> bool {
+    text.len() > 2 && text.starts_with(':') && text.ends_with(':') && !text.contains(' ')
+}
+
+fn footnote_definition(line: &str) -> Option<(String, &str)> {
+    if !line.starts_with("[fn:") {
+        return None;
+    }
+    let end = line.find(']')?;
+    let label = &line[4..end];
+    if label.is_empty() || label.contains(':') {
+        return None;
+    }
+    Some((label.to_owned(), line[(end + 1)..].trim()))
+}
+
+/// `#+BEGIN_SRC swift` returns `SRC`
+fn block_begin(line: &str) -> Option<String> {
+    if !line.to_uppercase().starts_with("#+BEGIN_") {
+        return None;
+    }
+    line[8..].split_whitespace().nth(0).map(|n| n.to_uppercase())
+}
+
+/// The index of the matching `#+END_` line, or the end of the document
+fn block_end(lines: &[&str], start: usize, name: &str) -> usize {
+    let end = format!("#+END_{}", name);
+    (start..lines.len())
+        .find(|index| lines[*index].trim().to_uppercase().starts_with(&end))
+        .unwrap_or(lines.len())
+}
+
+fn ends_paragraph(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty()
+        || trimmed.starts_with("#+")
+        || trimmed.starts_with('|')
+        || trimmed.starts_with(": ")
+        || heading(line).is_some()
+        || list_item(line).is_some()
+        || footnote_definition(line).is_some()
+}
+
+fn take_while<F>(lines: &[&str], start: usize, predicate: F) -> usize where F: Fn(&str) -> bool {
+    (start..lines.len())
+        .find(|index| !predicate(lines[*index]))
+        .unwrap_or(lines.len())
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_left_matches(' ').len()
+}
+
+/// Org escapes `*` and `#+` at the beginning of lines in blocks with a comma
+fn unescape_line(line: &str) -> &str {
+    let trimmed = line.trim_left();
+    if trimmed.starts_with(",*") || trimmed.starts_with(",#+") {
+        let comma = line.len() - trimmed.len();
+        return &line[(comma + 1)..];
+    }
+    line
+}
+
+fn is_image(url: &str) -> bool {
+    let lowercased = url.to_lowercase();
+    [".png", ".jpg", ".jpeg", ".gif", ".svg"].iter().any(|e| lowercased.ends_with(e))
+}
+
+fn closing_bracket(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    for index in start..chars.len() {
+        match chars[index] {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+fn starts_with(chars: &[char], index: usize, pattern: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    chars.len() >= index + pattern.len() && chars[index..(index + pattern.len())] == pattern[..]
+}
+
+fn find(chars: &[char], start: usize, pattern: &str) -> Option<usize> {
+    (start..chars.len()).find(|index| starts_with(chars, *index, pattern))
+}
+
+/// Emphasis markers only count if they are preceded by whitespace or punctuation
+/// and followed by a non whitespace character
+fn can_open(chars: &[char], index: usize) -> bool {
+    let pre = index == 0 || chars[index - 1].is_whitespace() || "-('\"{".contains(chars[index - 1]);
+    pre && chars.get(index + 1).map_or(false, |c| !c.is_whitespace())
+}
+
+fn find_closing(chars: &[char], start: usize) -> Option<usize> {
+    let marker = chars[start];
+    ((start + 1)..chars.len()).find(|index| {
+        let index = *index;
+        chars[index] == marker
+            && index > start + 1
+            && !chars[index - 1].is_whitespace()
+            && chars.get(index + 1).map_or(true, |c| c.is_whitespace() || "-.,;:!?')}\"[".contains(*c))
+    })
+}
+
+#[test]
+fn test_render_org() {
+    let contents = "#+title: Example
+#+OPTIONS: toc:nil
+* Introduction
+Some *bold*, /italic/ and =code= text with a [[https://swift.org][link]][fn:: A footnote].
+
+- first
+- second
+  1. nested
+
+| Name | Value |
+|------+-------|
+| a    | 1     |
+
+#+BEGIN_SRC swift :tangle example.swift
+let a = 1 < 2
+#+END_SRC
+** Details
+";
+    let html = render_org(contents, true);
+    assert!(html.starts_with("<h1 class=\"title\">Example</h1>\n<nav id=\"TOC\">"));
+    assert!(html.contains("<h1 id=\"sec-1\">Introduction</h1>"));
+    assert!(html.contains("<h2 id=\"sec-1-1\">Details</h2>"));
+    assert!(html.contains("<p>Some <strong>bold</strong>, <em>italic</em> and <code>code</code> text with a <a href=\"https://swift.org\">link</a><sup>"));
+    assert!(html.contains("<ul>\n<li>first</li>\n<li>second\n<ol>\n<li>nested</li>\n</ol></li>\n</ul>"));
+    assert!(html.contains("<thead>\n<tr><th>Name</th><th>Value</th></tr>\n</thead>"));
+    assert!(html.contains("<code class=\"sourceCode swift\">let a = 1 &lt; 2</code>"));
+    assert!(html.contains("<li id=\"fn1\">A footnote <a href=\"#fnref1\">↩</a></li>"));
+}
+
+#[test]
+fn test_toc_skipped_level() {
+    let html = render_org("* One\n*** Deep\n** Two\n* Three\n", true);
+    assert!(html.contains("<li><a href=\"#sec-1\">One</a><ul>\n<li><a href=\"#sec-1-0-1\">Deep</a></li>\n\
+                           <li><a href=\"#sec-1-1\">Two</a></li>\n</ul>\n</li>\n<li><a href=\"#sec-2\">Three</a></li>\n</ul>"));
+    assert!(!html.contains("<ul>\n<ul>"));
+}
+
+#[test]
+fn test_more_marker() {
+    let html = render_org("Intro\n#+more\nRest\n", false);
+    let marker = html.find("<!--more-->").unwrap();
+    assert!(html[..marker].contains("Intro"));
+    assert!(html[marker..].contains("Rest"));
+}
@@ -0,0 +1,100 @@
+use org_parser::fileinfo::FileInfo;
+use renderer::DuneRenderer;
+
+use sha2::{Sha256, Digest};
+
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, prelude::*};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::str;
+use std::sync::Mutex;
+
+// Include the pandoc template
+const PANDOC_HTML: &'static str = include_str!("pandoc.html");
+
+fn spit<T: AsRef<Path>>(path: T, contents: &str) -> Result<(), io::Error> {
+    let mut file = File::create(path)?;
+    file.write_all(contents.as_bytes())
+}
+
+/// Shells out to pandoc, which has to be installed. Pandoc detects the input
+/// format based on the file extension.
+pub struct PandocRenderer {
+    extensions: Vec<&'static str>,
+    /// `pandoc --version`, only determined once
+    version: Mutex<Option<String>>,
+    /// The template file, only written once so that the parser
+    /// threads don't read it while it is being rewritten
+    template: Mutex<Option<PathBuf>>,
+}
+
+impl PandocRenderer {
+    /// `extensions` are the file types for which pandoc replaces the built-in
+    /// renderers. Without extensions, it is only used via `#+renderer: pandoc`.
+    pub fn new(extensions: Vec<&'static str>) -> PandocRenderer {
+        PandocRenderer {
+            extensions,
+            version: Mutex::new(None),
+            template: Mutex::new(None)
+        }
+    }
+
+    fn version(&self) -> String {
+        let mut version = self.version.lock().unwrap();
+        if version.is_none() {
+            let output = Command::new("pandoc").arg("--version").output();
+            *version = Some(match output {
+                Ok(output) => String::from_utf8_lossy(&output.stdout).lines().nth(0).unwrap_or("").to_owned(),
+                Err(_) => "missing".to_owned()
+            });
+        }
+        version.clone().unwrap()
+    }
+
+    fn template(&self) -> Result<PathBuf, io::Error> {
+        let mut template = self.template.lock().unwrap();
+        if template.is_none() {
+            // One template per process, so that concurrent builds don't overwrite each other's template
+            let path = env::temp_dir().join(format!("wanderdune-template-{}.html", process::id()));
+            spit(&path, PANDOC_HTML)?;
+            *template = Some(path);
+        }
+        Ok(template.clone().unwrap())
+    }
+}
+
+impl DuneRenderer for PandocRenderer {
+    fn name(&self) -> &str {
+        "pandoc"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+
+    fn fingerprint(&self, info: &FileInfo) -> String {
+        let mut hasher = Sha256::default();
+        hasher.input(PANDOC_HTML.as_bytes());
+        format!("{} template {:x} toc {}", self.version(), hasher.result(), info.has_inline_toc())
+    }
+
+    fn render(&self, path: &Path, info: &FileInfo) -> Result<String, Box<Error>> {
+        let template = self.template()?.to_string_lossy().into_owned();
+        let mut args: Vec<&str> = vec!["--template", &template, "-s", "--highlight-style", "zenburn"];
+        if info.has_inline_toc() {
+            args.push("--toc");
+        }
+        let output = Command::new("pandoc")
+            .args(&args)
+            .arg(path.to_str().unwrap())
+            .output()?;
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stderr).trim().to_owned();
+            return Err(From::from(format!("pandoc failed: {}", message)));
+        }
+        Ok(str::from_utf8(&output.stdout)?.to_owned())
+    }
+}
@@ -0,0 +1,178 @@
+use org_parser::fileinfo::FileInfo;
+use org_parser::org_error::{OrgError, OrgErrorKind};
+
+use zip::{CompressionMethod, ZipWriter};
+use zip::write::FileOptions;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::io::prelude::*;
+use std::path::{Component, Path};
+
+/// How the `:tangle` files of a post are written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuneTangle {
+    /// As files in the folder of the post
+    Files,
+    /// As a zip file next to the post
+    Zip,
+}
+
+/// A `#+BEGIN_SRC` block of a post
+#[derive(Debug, Clone)]
+pub struct DuneSnippet {
+    pub identifier: String,
+    pub source: String,
+    pub line: usize,
+    pub language: String,
+    /// The file from `:tangle path`, `:tangle yes` uses the name of the post
+    pub tangle: Option<String>,
+    pub contents: String,
+}
+
+/// All source blocks of an org post. Other posts have no source blocks.
+pub fn snippets(identifier: &str, source: &Path) -> Result<Vec<DuneSnippet>, OrgError> {
+    if source.extension().and_then(|n| n.to_str()) != Some("org") {
+        return Ok(Vec::new());
+    }
+    let fileinfo = FileInfo::new(source)?;
+    Ok(fileinfo.sources.iter().map(|block| {
+        // Some posts have urls or typos where the language should be
+        let language = match block.language() {
+            Some(n) if n.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '+') => n.to_lowercase(),
+            _ => "text".to_owned(),
+        };
+        let tangle = match block.property(":tangle") {
+            None | Some("") | Some("no") => None,
+            Some("yes") => Some(format!("{}.{}", identifier, extension(&language))),
+            Some(path) => Some(path.to_owned()),
+        };
+        DuneSnippet {
+            identifier: identifier.to_owned(),
+            source: source.display().to_string(),
+            line: block.line,
+            language: language,
+            tangle: tangle,
+            contents: block.contents.clone(),
+        }
+    }).collect())
+}
+
+/// The tangled files and their contents. Blocks for the same file are
+/// joined, like org does. Paths have to stay inside the post folder.
+pub fn tangled_files(snippets: &[DuneSnippet]) -> Result<Vec<(String, String)>, OrgError> {
+    let mut files: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for snippet in snippets {
+        let path = match snippet.tangle {
+            Some(ref path) => path,
+            None => continue,
+        };
+        let is_inside = Path::new(path).components().all(|component| match component {
+            Component::Normal(_) | Component::CurDir => true,
+            _ => false,
+        });
+        if !is_inside {
+            return Err(OrgError::new(OrgErrorKind::MalformedMetadata,
+                                     format!("Can't tangle outside of the post: {}", path))
+                       .line(Some(snippet.line)));
+        }
+        files.entry(path.clone()).or_insert(Vec::new()).push(&snippet.contents);
+    }
+    Ok(files.into_iter()
+       .map(|(path, contents)| (path, contents.join("\n\n") + "\n"))
+       .collect())
+}
+
+pub fn write_files(folder: &Path, files: &[(String, String)]) -> io::Result<()> {
+    for &(ref name, ref contents) in files {
+        let path = folder.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        File::create(&path)?.write_all(contents.as_bytes())?;
+    }
+    Ok(())
+}
+
+pub fn write_zip(path: &Path, files: &[(String, String)]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for &(ref name, ref contents) in files {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        zip.write_all(contents.as_bytes())?;
+    }
+    zip.finish().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    Ok(())
+}
+
+/// Lists the snippets by language, i.e.
+/// `swift: 2 snippets` followed by `  posts/2018-01-10-optional-extensions.org:12`
+pub fn report(snippets: &[DuneSnippet]) -> String {
+    let mut languages: BTreeMap<&str, Vec<&DuneSnippet>> = BTreeMap::new();
+    for snippet in snippets {
+        languages.entry(&snippet.language).or_insert(Vec::new()).push(snippet);
+    }
+    let mut report = String::new();
+    for (language, snippets) in languages {
+        report.push_str(&format!("{}: {} snippets\n", language, snippets.len()));
+        for snippet in snippets {
+            match snippet.tangle {
+                Some(ref path) => report.push_str(&format!("  {}:{} -> {}\n", snippet.source, snippet.line, path)),
+                None => report.push_str(&format!("  {}:{}\n", snippet.source, snippet.line)),
+            }
+        }
+    }
+    report
+}
+
+/// Write every snippet to `<folder>/<language>/<identifier>-<line>.<extension>`,
+/// so that they can be compile checked. Returns the number of written snippets.
+pub fn export(folder: &Path, snippets: &[DuneSnippet]) -> io::Result<usize> {
+    let files: Vec<(String, String)> = snippets.iter().map(|snippet| {
+        (format!("{}/{}-{}.{}", snippet.language, snippet.identifier, snippet.line, extension(&snippet.language)),
+         snippet.contents.clone() + "\n")
+    }).collect();
+    write_files(folder, &files)?;
+    Ok(files.len())
+}
+
+fn extension(language: &str) -> &str {
+    match language {
+        "swift" => "swift",
+        "rust" => "rs",
+        "javascript" | "js" => "js",
+        "python" => "py",
+        "ruby" => "rb",
+        "clojure" => "clj",
+        "haskell" => "hs",
+        "objc" | "objective-c" => "m",
+        "c" => "c",
+        "sh" | "bash" | "shell" => "sh",
+        "html" => "html",
+        _ => "txt",
+    }
+}
+
+#[test]
+fn test_tangled_files() {
+    let snippet = |line: usize, tangle: Option<&str>, contents: &str| DuneSnippet {
+        identifier: "2018-01-10-optional-extensions".to_owned(),
+        source: "posts/2018-01-10-optional-extensions.org".to_owned(),
+        line: line,
+        language: "swift".to_owned(),
+        tangle: tangle.map(String::from),
+        contents: contents.to_owned(),
+    };
+    let snippets = vec![snippet(3, Some("main.swift"), "let a = 1"),
+                        snippet(9, None, "a"),
+                        snippet(12, Some("main.swift"), "print(a)")];
+    assert_eq!(tangled_files(&snippets).unwrap(),
+               vec![("main.swift".to_owned(), "let a = 1\n\nprint(a)\n".to_owned())]);
+    assert!(report(&snippets).starts_with("swift: 3 snippets\n"));
+    assert!(tangled_files(&[snippet(1, Some("../escape.swift"), "")]).is_err());
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+/// How a tag is shown on the blog
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuneTagInfo {
+    /// I.e. `iOS` for `ios`
+    pub name: String,
+    pub description: String,
+}
+
+/// A group of tags, i.e. `Swift Topics` with `generics` and `enums`.
+/// Categories can be below other categories.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuneCategory {
+    pub identifier: String,
+    pub name: String,
+    pub description: String,
+    /// The identifier of the category above this one
+    pub parent: Option<String>,
+    /// Normalized tags
+    pub tags: Vec<String>,
+}
+
+/// The tags of the blog: `iOS` and `ios` are the same tag,
+/// `objetive-c` is an alias for `objective-c`. Built like
+/// `DuneTaxonomy::new().alias("objetive-c", "objective-c").category("swift", "Swift Topics", None, &["swift"])`
+#[derive(Debug, Clone, Default)]
+pub struct DuneTaxonomy {
+    aliases: HashMap<String, String>,
+    tags: HashMap<String, DuneTagInfo>,
+    categories: Vec<DuneCategory>,
+}
+
+impl DuneTaxonomy {
+    pub fn new() -> DuneTaxonomy {
+        DuneTaxonomy::default()
+    }
+
+    /// Posts tagged with `alias` are tagged with `tag`. Tags and categories
+    /// that were added before use the alias as well.
+    pub fn alias(mut self, alias: &str, tag: &str) -> Self {
+        self.aliases.insert(fold(alias), fold(tag));
+        let tags: Vec<(String, DuneTagInfo)> = self.tags.drain().collect();
+        for (tag, info) in tags {
+            let tag = self.normalize(&tag);
+            self.tags.insert(tag, info);
+        }
+        let mut categories = ::std::mem::replace(&mut self.categories, Vec::new());
+        for category in categories.iter_mut() {
+            category.tags = self.normalize_all(&category.tags);
+        }
+        self.categories = categories;
+        self
+    }
+
+    pub fn tag(mut self, tag: &str, name: &str, description: &str) -> Self {
+        self.tags.insert(self.normalize(tag), DuneTagInfo {
+            name: name.to_owned(),
+            description: description.to_owned(),
+        });
+        self
+    }
+
+    pub fn category(mut self, identifier: &str, name: &str, parent: Option<&str>, tags: &[&str]) -> Self {
+        let tags = tags.iter().map(|tag| self.normalize(tag)).collect();
+        self.categories.push(DuneCategory {
+            identifier: identifier.to_owned(),
+            name: name.to_owned(),
+            description: String::new(),
+            parent: parent.map(|parent| parent.to_owned()),
+            tags: tags,
+        });
+        self
+    }
+
+    /// The description of the category that was added last
+    pub fn described(mut self, description: &str) -> Self {
+        if let Some(category) = self.categories.last_mut() {
+            category.description = description.to_owned();
+        }
+        self
+    }
+
+    /// The tag in lowercase, or the tag it is an alias for
+    pub fn normalize(&self, tag: &str) -> String {
+        let tag = fold(tag);
+        match self.aliases.get(&tag) {
+            Some(target) => target.clone(),
+            None => tag,
+        }
+    }
+
+    /// Normalized tags, without duplicates, in their original order
+    pub fn normalize_all(&self, tags: &[String]) -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = self.normalize(tag);
+            if !normalized.contains(&tag) {
+                normalized.push(tag);
+            }
+        }
+        normalized
+    }
+
+    pub fn tag_info(&self, tag: &str) -> Option<&DuneTagInfo> {
+        self.tags.get(tag)
+    }
+
+    pub fn categories(&self) -> &[DuneCategory] {
+        &self.categories
+    }
+
+    pub fn category_by_identifier(&self, identifier: &str) -> Option<&DuneCategory> {
+        self.categories.iter().find(|category| category.identifier == identifier)
+    }
+
+    /// The categories directly below `parent`, or the top categories for `None`
+    pub fn children(&self, parent: Option<&str>) -> Vec<&DuneCategory> {
+        self.categories.iter()
+            .filter(|category| category.parent.as_ref().map(|n| n.as_str()) == parent)
+            .collect()
+    }
+
+    /// The tags of the category and of all the categories below it
+    pub fn category_tags(&self, identifier: &str) -> Vec<&str> {
+        let mut tags: Vec<&str> = Vec::new();
+        let mut visited: Vec<&str> = Vec::new();
+        let mut pending: Vec<&str> = vec![identifier];
+        while let Some(current) = pending.pop() {
+            // Categories that are each other's parent are only visited once
+            if visited.contains(&current) {
+                continue;
+            }
+            visited.push(current);
+            if let Some(category) = self.category_by_identifier(current) {
+                tags.extend(category.tags.iter().map(|tag| tag.as_str()));
+            }
+            pending.extend(self.children(Some(current)).iter().rev().map(|child| child.identifier.as_str()));
+        }
+        tags
+    }
+
+    /// The identifiers of the categories that one of the tags belongs to,
+    /// including the categories above them
+    pub fn categories_for(&self, tags: &[String]) -> Vec<String> {
+        let mut identifiers: Vec<String> = Vec::new();
+        for category in &self.categories {
+            if category.tags.iter().any(|tag| tags.contains(tag)) {
+                let mut current = Some(category);
+                while let Some(category) = current {
+                    if identifiers.contains(&category.identifier) {
+                        break;
+                    }
+                    identifiers.push(category.identifier.clone());
+                    current = category.parent.as_ref().and_then(|parent| self.category_by_identifier(parent));
+                }
+            }
+        }
+        identifiers
+    }
+}
+
+fn fold(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+#[test]
+fn test_taxonomy() {
+    let taxonomy = DuneTaxonomy::new()
+        .alias("objetive-c", "objective-c")
+        .tag("iOS", "iOS", "Apps for iPhone and iPad")
+        .category("apple", "Apple", None, &["ios"])
+        .category("languages", "Languages", Some("apple"), &["Objective-C", "swift"]);
+    let tags = taxonomy.normalize_all(&["iOS".to_owned(), "ios".to_owned(), "Objetive-C".to_owned()]);
+    assert_eq!(tags, vec!["ios", "objective-c"]);
+    assert_eq!(taxonomy.tag_info("ios").map(|info| info.name.as_str()), Some("iOS"));
+    assert_eq!(taxonomy.categories_for(&["swift".to_owned()]), vec!["languages", "apple"]);
+    assert_eq!(taxonomy.category_tags("apple"), vec!["ios", "objective-c", "swift"]);
+
+    // The order of `alias` and `category` doesn't matter
+    let taxonomy = DuneTaxonomy::new()
+        .category("apple", "Apple", None, &["objetive-c"])
+        .tag("objetive-c", "Objective-C", "")
+        .alias("objetive-c", "objective-c");
+    assert_eq!(taxonomy.categories_for(&["objective-c".to_owned()]), vec!["apple"]);
+    assert!(taxonomy.tag_info("objective-c").is_some());
+}
@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet};
+
+use utils::slugify;
+
+/// A heading of a post with the headings below it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuneHeading {
+    /// 1 for `<h1>`, 2 for `<h2>`, ...
+    pub level: usize,
+    /// The text without tags, entities are kept
+    pub text: String,
+    /// The id of the heading, for `#pattern-matching` links
+    pub id: String,
+    pub children: Vec<DuneHeading>,
+}
+
+/// Give every heading a stable id from its text, `Pattern Matching` is
+/// `pattern-matching`, and return the html with the tree of headings.
+/// Links to the ids from the renderer are updated. The title of the
+/// post (`<h1 class="title">`) is not a heading.
+pub fn anchor_headings(html: &str) -> (String, Vec<DuneHeading>) {
+    let mut out = String::with_capacity(html.len());
+    let mut headings: Vec<DuneHeading> = Vec::new();
+    let mut renamed: Vec<(String, String)> = Vec::new();
+    let mut issued: HashSet<String> = HashSet::new();
+    let mut rest = html;
+    while let Some((start, level)) = next_heading(rest) {
+        let open_end = match rest[start..].find('>') {
+            Some(n) => start + n,
+            None => break,
+        };
+        let closing = format!("</h{}>", level);
+        let close_start = match rest[open_end..].find(&closing) {
+            Some(n) => open_end + n,
+            None => break,
+        };
+        let attributes = &rest[start + 3..open_end];
+        let inner = &rest[open_end + 1..close_start];
+        out.push_str(&rest[..start]);
+        if attributes.contains("class=\"title\"") {
+            out.push_str(&rest[start..close_start + closing.len()]);
+            rest = &rest[close_start + closing.len()..];
+            continue;
+        }
+
+        let text = strip(inner).trim().to_owned();
+        let mut id = slugify(&decode(&text));
+        if id.is_empty() {
+            id = "section".to_owned();
+        }
+        // The second `Example` is `example-2`, unless an `Example 2` took it
+        let mut unused = id.clone();
+        let mut suffix = 1;
+        while issued.contains(&unused) {
+            suffix += 1;
+            unused = format!("{}-{}", id, suffix);
+        }
+        id = unused;
+        issued.insert(id.clone());
+
+        let attributes = match attribute(attributes, "id") {
+            Some(old) => {
+                renamed.push((old.to_owned(), id.clone()));
+                attributes.replace(&format!("id=\"{}\"", old), &format!("id=\"{}\"", id))
+            }
+            None => format!(" id=\"{}\"{}", id, attributes),
+        };
+        out.push_str(&format!("<h{}{}>{}{}", level, attributes, inner, closing));
+        headings.push(DuneHeading {
+            level: level,
+            text: text,
+            id: id,
+            children: Vec::new(),
+        });
+        rest = &rest[close_start + closing.len()..];
+    }
+    out.push_str(rest);
+
+    let renamed: HashMap<String, String> = renamed.into_iter()
+        .filter(|&(ref old, ref new)| old != new)
+        .collect();
+    if !renamed.is_empty() {
+        out = rewrite_links(&out, &renamed);
+    }
+    let mut index = 0;
+    (out, nest(&headings, &mut index, 0))
+}
+
+/// All headings, depth first, for a flat table of contents
+pub fn flatten(headings: &[DuneHeading]) -> Vec<&DuneHeading> {
+    let mut flat: Vec<&DuneHeading> = Vec::new();
+    for heading in headings {
+        flat.push(heading);
+        flat.extend(flatten(&heading.children));
+    }
+    flat
+}
+
+/// Point `href="#old"` to `href="#new"` in one pass, so that
+/// `sec-1` to `intro` and `intro` to `intro-2` don't chain
+fn rewrite_links(html: &str, renamed: &HashMap<String, String>) -> String {
+    let prefix = "href=\"#";
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find(prefix) {
+        let id_start = start + prefix.len();
+        out.push_str(&rest[..id_start]);
+        rest = &rest[id_start..];
+        let end = rest.find('"').unwrap_or(rest.len());
+        match renamed.get(&rest[..end]) {
+            Some(new) => out.push_str(new),
+            None => out.push_str(&rest[..end]),
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The position and the level of the next `<h1>` to `<h6>`
+fn next_heading(html: &str) -> Option<(usize, usize)> {
+    let bytes = html.as_bytes();
+    let mut offset = 0;
+    while let Some(n) = html[offset..].find("<h") {
+        let start = offset + n;
+        if let (Some(&level), Some(&after)) = (bytes.get(start + 2), bytes.get(start + 3)) {
+            if level >= b'1' && level <= b'6' && (after == b'>' || after == b' ') {
+                return Some((start, (level - b'0') as usize));
+            }
+        }
+        offset = start + 2;
+    }
+    None
+}
+
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let prefix = format!("{}=\"", name);
+    let start = attributes.find(&prefix)? + prefix.len();
+    let end = attributes[start..].find('"')?;
+    Some(&attributes[start..start + end])
+}
+
+fn strip(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    text
+}
+
+fn decode(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// The headings below `level`, up to the next heading on `level`
+fn nest(flat: &[DuneHeading], index: &mut usize, level: usize) -> Vec<DuneHeading> {
+    let mut headings: Vec<DuneHeading> = Vec::new();
+    while *index < flat.len() && flat[*index].level > level {
+        let mut heading = flat[*index].clone();
+        *index += 1;
+        heading.children = nest(flat, index, heading.level);
+        headings.push(heading);
+    }
+    headings
+}
+
+#[test]
+fn test_anchor_headings() {
+    let html = "<h1 class=\"title\">Patterns</h1>\n<nav id=\"TOC\"><a href=\"#sec-1\">Intro</a></nav>\n\
+                <h1 id=\"sec-1\">Intro</h1>\n<h2>Tuples &amp; <code>enums</code></h2>\n<h2>Example</h2>\n\
+                <h1>Example</h1>\n";
+    let (html, toc) = anchor_headings(html);
+    assert!(html.contains("<a href=\"#intro\">Intro</a>"));
+    assert!(html.contains("<h2 id=\"tuples-enums\">Tuples &amp; <code>enums</code></h2>"));
+    assert!(html.contains("<h1 id=\"example-2\">Example</h1>"));
+    assert_eq!(toc.len(), 2);
+    assert_eq!(flatten(&toc).iter().map(|h| h.id.as_str()).collect::<Vec<&str>>(),
+               vec!["intro", "tuples-enums", "example", "example-2"]);
+    // `intro` was `sec-1`, the link to the old `intro` doesn't follow it
+    let (html, _) = anchor_headings("<a href=\"#sec-1\">1</a><a href=\"#intro\">2</a>\
+                                     <h2 id=\"sec-1\">Intro</h2><h2 id=\"intro\">Intro</h2>");
+    assert!(html.starts_with("<a href=\"#intro\">1</a><a href=\"#intro-2\">2</a>"));
+    assert_eq!(toc[0].children.iter().map(|h| h.id.as_str()).collect::<Vec<&str>>(),
+               vec!["tuples-enums", "example"]);
+    // `Example 2` doesn't get the id of the second `Example`
+    let (_, toc) = anchor_headings("<h2>Example</h2><h2>Example</h2><h2>Example 2</h2>");
+    assert_eq!(toc.iter().map(|h| h.id.as_str()).collect::<Vec<&str>>(),
+               vec!["example", "example-2", "example-2-2"]);
+}
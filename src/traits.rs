@@ -1,3 +1,14 @@
+use dune_diagnostic::DuneParseResult;
+
 pub trait DunePathBuilder {
     fn push<T: AsRef<str>>(mut self, path: T) -> Self;
 }
+
+/// A source of posts, i.e. a folder of org files or a folder of
+/// markdown files with front matter. `Dune` collects the posts
+/// of all its sources.
+pub trait DunePostSource {
+    /// The posts that could be parsed and diagnostics for everything
+    /// that went wrong along the way
+    fn parse(&self) -> DuneParseResult;
+}
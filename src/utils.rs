@@ -1,4 +1,11 @@
+use std::cmp;
 use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::panic;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
 
 use ammonia::Builder;
 
@@ -9,12 +16,212 @@ pub fn strip_tags(html: &str) -> String {
         .to_string()
 }
 
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Elements without a closing tag
+const VOID_TAGS: &'static [&'static str] = &["area", "br", "col", "embed", "hr", "img", "input",
+                                             "link", "meta", "param", "source", "track", "wbr"];
+
+/// The first `words` words of the html, cut on a word boundary and with
+/// `…` if something was cut. Tags that are still open are closed.
+pub fn truncate_html(html: &str, words: usize) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut open: Vec<String> = Vec::new();
+    let mut count = 0;
+    let mut in_word = false;
+    let mut truncated = false;
+    let mut rest = html;
+    'outer: while !rest.is_empty() {
+        if rest.starts_with('<') {
+            let end = match rest.find('>') {
+                Some(n) => n + 1,
+                None => break,
+            };
+            let tag = &rest[..end];
+            out.push_str(tag);
+            rest = &rest[end..];
+            // Comments, doctypes and `<br/>`
+            if tag.starts_with("<!") || tag.ends_with("/>") {
+                continue;
+            }
+            let name: String = tag.trim_left_matches('<').trim_left_matches('/').chars()
+                .take_while(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase();
+            if name.is_empty() || VOID_TAGS.contains(&name.as_str()) {
+                continue;
+            }
+            if tag.starts_with("</") {
+                if let Some(index) = open.iter().rposition(|other| other == &name) {
+                    open.truncate(index);
+                }
+            } else {
+                open.push(name);
+            }
+            continue;
+        }
+        let end = rest.find('<').unwrap_or(rest.len());
+        let text = &rest[..end];
+        for (index, c) in text.char_indices() {
+            if c.is_whitespace() {
+                in_word = false;
+            } else if !in_word {
+                in_word = true;
+                if count == words {
+                    out.push_str(text[..index].trim_right());
+                    truncated = true;
+                    break 'outer;
+                }
+                count += 1;
+            }
+        }
+        out.push_str(text);
+        rest = &rest[end..];
+    }
+    if truncated {
+        out.push_str("…");
+    }
+    for name in open.iter().rev() {
+        out.push_str(&format!("</{}>", name));
+    }
+    out
+}
+
+/// The first `words` words of the text, with `…` if something was cut
+pub fn truncate_words(text: &str, words: usize) -> String {
+    let mut parts: Vec<&str> = text.split_whitespace().take(words + 1).collect();
+    if parts.len() > words {
+        parts.truncate(words);
+        return parts.join(" ") + "…";
+    }
+    parts.join(" ")
+}
+
+/// A lowercase name for urls and ids: `Pattern Matching, Part 2` is `pattern-matching-part-2`
+pub fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+/// The files that start a bundle: a folder with the post and its assets
+const BUNDLE_INDEX: &'static [&'static str] = &["index.org", "index.md", "index.markdown"];
+
+/// All files in `folder` and its subfolders, sorted. In a bundle folder,
+/// only the index file is returned, the other files are its assets.
+pub fn post_files(folder: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        // Hidden files and folders, i.e. `.git` or editor backups
+        .filter(|path| !path.file_name().and_then(|n| n.to_str()).map_or(false, |n| n.starts_with(".")))
+        .collect();
+    entries.sort();
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in entries {
+        if path.is_dir() {
+            match BUNDLE_INDEX.iter().map(|index| path.join(index)).find(|index| index.is_file()) {
+                Some(index) => files.push(index),
+                // Unreadable subfolders are skipped
+                None => files.extend(post_files(&path).unwrap_or(Vec::new())),
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// The name of a post: the name of the file without extension,
+/// or the name of the folder for bundles (`<name>/index.org`)
+pub fn post_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem().and_then(|n| n.to_str())?;
+    if stem != "index" {
+        return Some(stem.to_owned());
+    }
+    path.parent()
+        .and_then(|n| n.file_name())
+        .and_then(|n| n.to_str())
+        .map(String::from)
+}
+
+/// Runs `action` for all items on at most `max_threads` threads.
+/// The results are in the same order as the items.
+pub fn parallel_map<T, R, F>(items: Vec<T>, max_threads: usize, action: F) -> Vec<R>
+    where T: Send + 'static, R: Send + 'static, F: Fn(T) -> R + Send + Sync + 'static
+{
+    let count = items.len();
+    // Reversed, so that the workers pop the items in order
+    let queue: Vec<(usize, T)> = items.into_iter().enumerate().rev().collect();
+    let queue = Arc::new(Mutex::new(queue));
+    let action = Arc::new(action);
+    let (sender, receiver) = mpsc::channel();
+    let workers: Vec<thread::JoinHandle<()>> = (0..cmp::max(1, cmp::min(max_threads, count))).map(|_| {
+        let queue = Arc::clone(&queue);
+        let action = Arc::clone(&action);
+        let sender = sender.clone();
+        thread::spawn(move || loop {
+            let next = queue.lock().unwrap().pop();
+            match next {
+                Some((index, item)) => sender.send((index, action(item))).unwrap(),
+                None => break,
+            }
+        })
+    }).collect();
+    drop(sender);
+    let mut results: Vec<(usize, R)> = receiver.iter().collect();
+    for worker in workers {
+        if let Err(e) = worker.join() {
+            panic::resume_unwind(e);
+        }
+    }
+    results.sort_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
 mod UtilTest {
-    use super::strip_tags;
+    use super::{strip_tags, parallel_map, post_name, slugify, truncate_html, truncate_words};
+    use std::path::Path;
     #[test]
     fn test_strip_tags() {
         let input = "hey, <b>my name is <i>carl</i>";
         let input = strip_tags(input).to_string();
         assert_eq!(input, "hey, my name is carl");
     }
+
+    #[test]
+    fn test_parallel_map() {
+        let input: Vec<usize> = (0..100).collect();
+        let output = parallel_map(input, 4, |n| n * 2);
+        assert_eq!(output, (0..100).map(|n| n * 2).collect::<Vec<usize>>());
+        assert_eq!(parallel_map(vec![1], 0, |n: i32| n + 1), vec![2]);
+    }
+
+    #[test]
+    fn test_post_name() {
+        assert_eq!(post_name(Path::new("posts/2018-05-01-expanding-swifts-reach/index.org")),
+                   Some("2018-05-01-expanding-swifts-reach".to_owned()));
+        assert_eq!(post_name(Path::new("posts/2018/2018-01-10-optional-extensions.org")),
+                   Some("2018-01-10-optional-extensions".to_owned()));
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Pattern Matching in Swift, Part 2"), "pattern-matching-in-swift-part-2");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate_html("<p>Pattern <em>matching in</em> Swift</p><p>More</p>", 2),
+                   "<p>Pattern <em>matching…</em></p>");
+        assert_eq!(truncate_html("<p>Größe<br>ändern</p>", 5), "<p>Größe<br>ändern</p>");
+        assert_eq!(truncate_words("Größe ändern  leicht gemacht", 3), "Größe ändern leicht…");
+    }
 }